@@ -0,0 +1,543 @@
+//! `shortcut` - list and launch shortcut-rs shortcuts from a terminal.
+//!
+//! Works on the same config file as the tray app.
+
+use clap::{Args, Parser, Subcommand};
+use shortcut_core::sequence::{self, Step, StepRunner, Wait};
+use shortcut_core::template::{self, Param};
+use shortcut_core::{
+    get_config_path, launcher, list_backups, new_shortcut_id, read_processes, set_config_path, AppConfig, CommandArgs, ConfigStore,
+    LaunchError, LaunchOptions, Launcher, ProcessInfo, ProcessStatus, SequenceStep, Shortcut, ShortcutType,
+};
+use std::collections::BTreeMap;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Parser)]
+#[command(name = "shortcut", version, about = "List and launch shortcut-rs shortcuts")]
+struct Cli {
+    /// Config file to use instead of the default location
+    #[arg(long, global = true, value_name = "PATH", env = "SHORTCUTS_CONFIG")]
+    config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List configured shortcuts
+    List {
+        /// Print the shortcuts as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Launch a shortcut by name or ID
+    Run {
+        name: String,
+        /// Value for a {{placeholder}}; the others are asked for on the terminal
+        #[arg(long = "set", value_name = "NAME=VALUE", value_parser = parse_env_var)]
+        values: Vec<(String, String)>,
+    },
+    /// Add a new shortcut
+    Add {
+        name: String,
+        #[command(flatten)]
+        fields: ShortcutFields,
+    },
+    /// Remove a shortcut by name or ID
+    Remove {
+        name: String,
+    },
+    /// Change fields of an existing shortcut
+    Edit {
+        name: String,
+        /// New display name
+        #[arg(long)]
+        rename: Option<String>,
+        #[command(flatten)]
+        fields: ShortcutFields,
+    },
+    /// Copy the config file to PATH
+    Export {
+        path: PathBuf,
+    },
+    /// Replace the config with the one at PATH
+    Import {
+        path: PathBuf,
+    },
+    /// List or restore automatic config backups
+    Backup {
+        #[command(subcommand)]
+        command: BackupCommand,
+    },
+    /// List processes the tray app has launched and how finished ones ended
+    Ps {
+        /// Print the processes as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Stop a process listed by `ps`
+    Kill {
+        /// Run ID as shown by `ps` (a unique prefix is enough)
+        run_id: String,
+    },
+    /// Show or set the terminal visible scripts open in on Linux
+    Terminal {
+        /// Terminal name or command line, e.g. kitty or "foot -e"; empty to detect one
+        terminal: Option<String>,
+    },
+    /// Print the config file location
+    Path,
+}
+
+#[derive(Subcommand)]
+enum BackupCommand {
+    /// List backups, newest first
+    List,
+    /// Replace the config with a backup (the current config is backed up first)
+    Restore {
+        /// Backup file name as shown by `backup list`
+        name: String,
+    },
+}
+
+/// Shortcut fields shared by `add` and `edit`; unset flags leave the field untouched
+#[derive(Args)]
+struct ShortcutFields {
+    /// Shortcut type: app, url, file, folder, script, shell or sequence
+    #[arg(long = "type", short = 't')]
+    shortcut_type: Option<ShortcutType>,
+    /// Executable, URL, file or folder path
+    #[arg(long, short = 'c')]
+    command: Option<String>,
+    /// Inline script content for shell shortcuts
+    #[arg(long)]
+    script: Option<String>,
+    /// Command-line arguments as one string, split with the platform's quoting rules
+    #[arg(long, allow_hyphen_values = true, conflicts_with = "arg")]
+    args: Option<String>,
+    /// A single argument passed as-is (repeatable; replaces all arguments)
+    #[arg(long, allow_hyphen_values = true)]
+    arg: Vec<String>,
+    /// Working directory
+    #[arg(long)]
+    working_dir: Option<String>,
+    /// Set an environment variable, e.g. --env KEY=VALUE (repeatable; KEY= removes it)
+    #[arg(long = "env", short = 'e', value_name = "KEY=VALUE", value_parser = parse_env_var)]
+    env: Vec<(String, String)>,
+    /// dotenv file loaded before --env values, relative to the config directory
+    #[arg(long)]
+    env_file: Option<String>,
+    /// Description shown in the popup
+    #[arg(long, short = 'd')]
+    description: Option<String>,
+    /// Shell for inline scripts (cmd, powershell, pwsh, sh, bash, zsh, fish, python, node or interpreter)
+    #[arg(long)]
+    shell: Option<String>,
+    /// Program running the script when --shell is interpreter
+    #[arg(long)]
+    interpreter: Option<String>,
+    /// Arguments for --interpreter; {script} stands for the script file, without it the script goes to stdin
+    #[arg(long, allow_hyphen_values = true)]
+    interpreter_args: Option<String>,
+    /// Run without a console window
+    #[arg(long)]
+    hidden: Option<bool>,
+    /// Run as administrator
+    #[arg(long)]
+    admin: Option<bool>,
+    /// Show a desktop notification when the run ends
+    #[arg(long)]
+    notify_on_exit: Option<bool>,
+    /// Keep the console or terminal window open after the script exits
+    #[arg(long)]
+    keep_open: Option<bool>,
+    /// Stop runs from the tray app after this many seconds (0 clears it)
+    #[arg(long, value_name = "SECS")]
+    timeout: Option<u64>,
+    /// Shortcut run by a sequence, by name or ID (repeatable; replaces all steps)
+    #[arg(long, value_name = "NAME")]
+    step: Vec<String>,
+}
+
+impl ShortcutFields {
+    /// Turn the names given to --step into IDs
+    fn resolve_steps(&mut self, cfg: &AppConfig) -> Result<(), String> {
+        for step in &mut self.step {
+            *step = cfg.shortcuts[find_index(cfg, step)?].id.clone();
+        }
+        Ok(())
+    }
+
+    fn apply(self, shortcut: &mut Shortcut) {
+        if let Some(shortcut_type) = self.shortcut_type {
+            shortcut.shortcut_type = shortcut_type;
+        }
+        if let Some(command) = self.command {
+            shortcut.command = command;
+        }
+        if let Some(script) = self.script {
+            shortcut.script = non_empty(script);
+        }
+        if let Some(args) = self.args {
+            shortcut.args = non_empty(args).map(CommandArgs::Line);
+        }
+        if !self.arg.is_empty() {
+            shortcut.args = Some(CommandArgs::List(self.arg));
+        }
+        if let Some(working_dir) = self.working_dir {
+            shortcut.working_dir = non_empty(working_dir);
+        }
+        for (key, value) in self.env {
+            if value.is_empty() {
+                shortcut.env.remove(&key);
+            } else {
+                shortcut.env.insert(key, value);
+            }
+        }
+        if let Some(env_file) = self.env_file {
+            shortcut.env_file = non_empty(env_file);
+        }
+        if let Some(description) = self.description {
+            shortcut.description = non_empty(description);
+        }
+        if let Some(shell) = self.shell {
+            shortcut.shell = non_empty(shell);
+        }
+        if let Some(interpreter) = self.interpreter {
+            shortcut.interpreter = non_empty(interpreter);
+        }
+        if let Some(interpreter_args) = self.interpreter_args {
+            shortcut.interpreter_args = non_empty(interpreter_args).map(CommandArgs::Line);
+        }
+        if let Some(hidden) = self.hidden {
+            shortcut.hidden = hidden;
+        }
+        if let Some(admin) = self.admin {
+            shortcut.admin = admin;
+        }
+        if let Some(notify_on_exit) = self.notify_on_exit {
+            shortcut.notify_on_exit = notify_on_exit;
+        }
+        if let Some(keep_open) = self.keep_open {
+            shortcut.keep_open = keep_open;
+        }
+        if let Some(timeout) = self.timeout {
+            shortcut.timeout_secs = Some(timeout).filter(|&secs| secs > 0);
+        }
+        if !self.step.is_empty() {
+            shortcut.steps = self
+                .step
+                .into_iter()
+                .map(|id| SequenceStep { id: Some(id), ..Default::default() })
+                .collect();
+        }
+    }
+}
+
+/// Passing an empty string clears an optional field
+fn non_empty(value: String) -> Option<String> {
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+/// Print what looks wrong about `shortcut` without refusing to save it
+fn warn(shortcut: &Shortcut) {
+    for warning in shortcut_core::env::warnings(shortcut) {
+        eprintln!("warning: {}", warning);
+    }
+}
+
+/// Starts the steps of a sequence run from the command line
+struct CliRunner {
+    terminal: Option<String>,
+}
+
+impl StepRunner for CliRunner {
+    fn start(&self, step: &Step) -> Result<Option<Wait>, LaunchError> {
+        let child = shortcut_core::launch_with(&step.shortcut, LaunchOptions { terminal: self.terminal.clone(), ..Default::default() })?;
+        Ok(child.map(|mut child| Box::new(move || child.wait().ok().and_then(|status| status.code())) as Wait))
+    }
+
+    fn failed(&self, shortcut: &Shortcut, error: &LaunchError) {
+        eprintln!("error: step '{}': {}", shortcut.name, error);
+    }
+}
+
+/// Parse a KEY=VALUE pair given to --env or --set
+fn parse_env_var(pair: &str) -> Result<(String, String), String> {
+    match pair.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("Expected KEY=VALUE, got '{}'", pair)),
+    }
+}
+
+/// Fill in `values` for the `params` not given with --set. On a terminal each is asked
+/// for, and an empty answer keeps the last value or the default; otherwise the last value
+/// is used and the default is left to [`template::fill`].
+fn ask_values(params: &[Param], values: &mut BTreeMap<String, String>) -> Result<(), String> {
+    let stdin = std::io::stdin();
+    for param in params {
+        if values.contains_key(&param.name) {
+            continue;
+        }
+        let suggested = param.last.clone().or_else(|| param.default.clone());
+        if !stdin.is_terminal() {
+            if let Some(last) = &param.last {
+                values.insert(param.name.clone(), last.clone());
+            }
+            continue;
+        }
+
+        let mut label = param.name.clone();
+        if !param.choices.is_empty() {
+            label.push_str(&format!(" ({})", param.choices.join("/")));
+        }
+        if let Some(suggested) = &suggested {
+            label.push_str(&format!(" [{}]", suggested));
+        }
+        eprint!("{}: ", label);
+        let mut answer = String::new();
+        stdin
+            .read_line(&mut answer)
+            .map_err(|e| format!("Failed to read {}: {}", param.name, e))?;
+        let answer = answer.trim_end_matches(['\r', '\n']);
+        // With nothing to fall back on, template::fill reports the value as missing
+        if let Some(value) = (!answer.is_empty()).then(|| answer.to_string()).or(suggested) {
+            values.insert(param.name.clone(), value);
+        }
+    }
+    Ok(())
+}
+
+/// Keep the values of `params` for the next launch of `shortcut`
+fn remember_values(shortcut: &Shortcut, params: &[Param], values: &BTreeMap<String, String>) {
+    if params.is_empty() {
+        return;
+    }
+    // Only a convenience for the next launch; not worth failing this one over
+    if let Err(e) = template::remember(&shortcut.id, values) {
+        eprintln!("Warning: {}", e);
+    }
+}
+
+/// Find a shortcut by ID, or by name ignoring case
+fn find_index(cfg: &AppConfig, name: &str) -> Result<usize, String> {
+    if let Some(index) = cfg.position(name) {
+        return Ok(index);
+    }
+
+    let matches: Vec<usize> = cfg
+        .shortcuts
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| s.name.eq_ignore_ascii_case(name))
+        .map(|(i, _)| i)
+        .collect();
+
+    match matches.as_slice() {
+        [index] => Ok(*index),
+        [] => Err(format!("No shortcut named '{}'", name)),
+        _ => Err(format!("More than one shortcut is named '{}', use its ID instead", name)),
+    }
+}
+
+fn list(store: &ConfigStore, json: bool) -> Result<(), String> {
+    let cfg = store.reload()?;
+    if json {
+        let out = serde_json::to_string_pretty(&cfg.shortcuts)
+            .map_err(|e| format!("Failed to serialize shortcuts: {}", e))?;
+        println!("{}", out);
+        return Ok(());
+    }
+
+    let width = cfg.shortcuts.iter().map(|s| s.name.len()).max().unwrap_or(0);
+    for shortcut in &cfg.shortcuts {
+        let target = match shortcut.shortcut_type {
+            ShortcutType::Shell => shortcut.script.as_deref().and_then(|s| s.lines().next()).unwrap_or("").to_string(),
+            ShortcutType::Sequence => format!("{} steps", shortcut.steps.len()),
+            _ => shortcut.command.clone(),
+        };
+        println!("{:<width$}  {:<8}  {}", shortcut.name, shortcut.shortcut_type, target, width = width);
+    }
+    Ok(())
+}
+
+/// Number of run ID characters shown by `ps`
+const SHORT_RUN_ID: usize = 8;
+
+fn describe_status(status: &ProcessStatus) -> String {
+    match status {
+        ProcessStatus::Running => "running".to_string(),
+        ProcessStatus::Exited { code: Some(code) } => format!("exit {}", code),
+        ProcessStatus::Exited { code: None } => "exited".to_string(),
+        ProcessStatus::Killed => "killed".to_string(),
+        ProcessStatus::TimedOut => "timeout".to_string(),
+    }
+}
+
+fn ps(json: bool) -> Result<(), String> {
+    let processes = read_processes()?;
+    if json {
+        let out = serde_json::to_string_pretty(&processes)
+            .map_err(|e| format!("Failed to serialize processes: {}", e))?;
+        println!("{}", out);
+        return Ok(());
+    }
+
+    for process in &processes {
+        println!(
+            "{:<8}  {:>7}  {:<8}  {}  {}",
+            &process.run_id[..SHORT_RUN_ID.min(process.run_id.len())],
+            process.pid,
+            describe_status(&process.status),
+            process.started,
+            process.shortcut_name
+        );
+    }
+    Ok(())
+}
+
+/// Find a process by its run ID or a unique prefix of it
+fn find_process(processes: &[ProcessInfo], run_id: &str) -> Result<ProcessInfo, String> {
+    let matches: Vec<&ProcessInfo> = processes.iter().filter(|p| p.run_id.starts_with(run_id)).collect();
+    match matches.as_slice() {
+        [process] => Ok((*process).clone()),
+        [] => Err(format!("No process with run ID '{}'", run_id)),
+        _ => Err(format!("Run ID '{}' is ambiguous, give more characters", run_id)),
+    }
+}
+
+fn run(cli: Cli) -> Result<(), String> {
+    if let Some(path) = cli.config {
+        set_config_path(path);
+    }
+
+    // Every change goes through the store, which locks out the tray app while it writes
+    let store = ConfigStore::default();
+
+    match cli.command {
+        Command::List { json } => list(&store, json),
+        Command::Run { name, values } => {
+            let cfg = store.reload()?;
+            let index = find_index(&cfg, &name)?;
+            let shortcut = &cfg.shortcuts[index];
+            let failed = |e: LaunchError| format!("Failed to launch '{}': {}", shortcut.name, e);
+            let params = template::params(shortcut, &cfg).map_err(failed)?;
+            let mut values: BTreeMap<String, String> = values.into_iter().collect();
+            ask_values(&params, &mut values)?;
+
+            if shortcut.shortcut_type == ShortcutType::Sequence {
+                let steps = sequence::resolve(shortcut, &cfg).and_then(|steps| sequence::fill(&steps, &values)).map_err(failed)?;
+                remember_values(shortcut, &params, &values);
+                // Runs in the foreground so delays and waits are not cut short
+                let runner = Arc::new(CliRunner { terminal: cfg.terminal.clone() });
+                if !sequence::run(&steps, &runner) {
+                    return Err(format!("Sequence '{}' stopped at a failed step", shortcut.name));
+                }
+                return Ok(());
+            }
+            let filled = template::fill(shortcut, &values).map_err(failed)?;
+            // Processes started here are not tracked; only the tray app keeps a registry
+            shortcut_core::launch_with(&filled, LaunchOptions { terminal: cfg.terminal.clone(), ..Default::default() }).map_err(failed)?;
+            remember_values(shortcut, &params, &values);
+            Ok(())
+        }
+        Command::Add { name, mut fields } => {
+            let mut shortcut = Shortcut {
+                id: new_shortcut_id(),
+                name,
+                shortcut_type: ShortcutType::App,
+                command: String::new(),
+                script: None,
+                args: None,
+                working_dir: None,
+                description: None,
+                hidden: false,
+                shell: None,
+                admin: false,
+                ..Default::default()
+            };
+            store.update(|cfg| {
+                fields.resolve_steps(cfg)?;
+                fields.apply(&mut shortcut);
+                warn(&shortcut);
+                cfg.shortcuts.push(shortcut);
+                Ok(())
+            })
+        }
+        Command::Remove { name } => store.update(|cfg| {
+            let index = find_index(cfg, &name)?;
+            cfg.shortcuts.remove(index);
+            Ok(())
+        }),
+        Command::Edit { name, rename, mut fields } => store.update(|cfg| {
+            fields.resolve_steps(cfg)?;
+            let index = find_index(cfg, &name)?;
+            let shortcut = &mut cfg.shortcuts[index];
+            if let Some(new_name) = rename {
+                shortcut.name = new_name;
+            }
+            fields.apply(shortcut);
+            warn(shortcut);
+            Ok(())
+        }),
+        Command::Export { path } => store.export(&path),
+        Command::Import { path } => store.import(&path),
+        Command::Backup { command: BackupCommand::List } => {
+            for backup in list_backups()? {
+                println!("{}  {}  {} bytes", backup.name, backup.created, backup.size);
+            }
+            Ok(())
+        }
+        Command::Backup { command: BackupCommand::Restore { name } } => store.restore_backup(&name),
+        Command::Ps { json } => ps(json),
+        Command::Kill { run_id } => {
+            let process = find_process(&read_processes()?, &run_id)?;
+            if !process.is_running() {
+                return Err(format!("{} is no longer running", process.shortcut_name));
+            }
+            let launcher = launcher::platform();
+            launcher.kill_process(process.pid)?;
+            // Whatever ignores the request is killed from a thread of this process, so
+            // stay until the process is gone or that has happened
+            let deadline = Instant::now() + launcher::KILL_GRACE + Duration::from_secs(1);
+            while launcher.is_running(process.pid) && Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(100));
+            }
+            Ok(())
+        }
+        Command::Terminal { terminal: None } => {
+            match store.reload()?.terminal {
+                Some(terminal) => println!("{}", terminal),
+                None => println!("(detected)"),
+            }
+            Ok(())
+        }
+        Command::Terminal { terminal: Some(terminal) } => store.update(|cfg| {
+            cfg.terminal = non_empty(terminal);
+            Ok(())
+        }),
+        Command::Path => {
+            println!("{}", get_config_path().display());
+            Ok(())
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
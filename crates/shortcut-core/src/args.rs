@@ -0,0 +1,299 @@
+//! Splitting and quoting command lines.
+//!
+//! A shortcut's `args` given as one string is split with the rules of the platform
+//! it runs on: `CommandLineToArgvW` on Windows and POSIX shell words elsewhere.
+//! [`join_args`] is the inverse, for launches that have to pass a single command
+//! line on, such as elevation prompts.
+
+/// Split `line` into arguments the way programs on this platform would see them
+pub fn split_args(line: &str) -> Result<Vec<String>, String> {
+    if cfg!(windows) {
+        Ok(split_windows(line))
+    } else {
+        split_unix(line)
+    }
+}
+
+/// Quote `args` into one command line that [`split_args`] turns back into `args`
+pub fn join_args(args: &[String]) -> String {
+    if cfg!(windows) {
+        join_windows(args)
+    } else {
+        join_unix(args)
+    }
+}
+
+/// Split like `CommandLineToArgvW` does for everything after the program name.
+///
+/// Arguments are separated by spaces and tabs outside double quotes. Backslashes are
+/// literal unless they precede a `"`: then each pair becomes one backslash, and an odd
+/// one out makes the quote literal. `""` inside quotes is a literal quote.
+pub fn split_windows(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    // An argument has started even if it is still empty, e.g. after ""
+    let mut started = false;
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' if !in_quotes => {
+                if started {
+                    args.push(std::mem::take(&mut current));
+                    started = false;
+                }
+            }
+            '\\' => {
+                let mut backslashes = 1;
+                while chars.peek() == Some(&'\\') {
+                    chars.next();
+                    backslashes += 1;
+                }
+                if chars.peek() == Some(&'"') {
+                    current.extend(std::iter::repeat_n('\\', backslashes / 2));
+                    if backslashes % 2 == 1 {
+                        chars.next();
+                        current.push('"');
+                    }
+                } else {
+                    current.extend(std::iter::repeat_n('\\', backslashes));
+                }
+                started = true;
+            }
+            '"' => {
+                if in_quotes && chars.peek() == Some(&'"') {
+                    chars.next();
+                    current.push('"');
+                } else {
+                    in_quotes = !in_quotes;
+                }
+                started = true;
+            }
+            c => {
+                current.push(c);
+                started = true;
+            }
+        }
+    }
+    if started {
+        args.push(current);
+    }
+    args
+}
+
+/// Quote `args` so that [`split_windows`] (and any program using the MSVC runtime)
+/// reads them back unchanged
+pub fn join_windows(args: &[String]) -> String {
+    let mut line = String::new();
+    for arg in args {
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        let quote = arg.is_empty() || arg.contains([' ', '\t']);
+        if quote {
+            line.push('"');
+        }
+        let mut backslashes = 0;
+        for c in arg.chars() {
+            match c {
+                '\\' => backslashes += 1,
+                '"' => {
+                    // Double the backslashes before a quote, then escape the quote itself
+                    line.extend(std::iter::repeat_n('\\', backslashes + 1));
+                    backslashes = 0;
+                }
+                _ => backslashes = 0,
+            }
+            line.push(c);
+        }
+        if quote {
+            // Backslashes before the closing quote must not escape it
+            line.extend(std::iter::repeat_n('\\', backslashes));
+            line.push('"');
+        }
+    }
+    line
+}
+
+// Characters cmd acts on outside quotes; `%` and `!` are covered since a caret inside
+// a variable name keeps it from being expanded
+const CMD_SPECIAL: &[char] = &['(', ')', '[', ']', '%', '!', '^', '"', '`', '<', '>', '&', '|', ';', ',', ' ', '*', '?'];
+
+/// Quote `args` into a command line for `cmd /S /C "..."`. Each argument is quoted like
+/// [`join_windows`], then every character cmd would act on is escaped with `^`, quotes
+/// included, so cmd runs a single command and passes the arguments on unchanged.
+pub fn join_cmd(args: &[String]) -> String {
+    let mut line = String::new();
+    for c in join_windows(args).chars() {
+        if CMD_SPECIAL.contains(&c) {
+            line.push('^');
+        }
+        line.push(c);
+    }
+    line
+}
+
+/// Split like a POSIX shell would, without expanding anything
+pub fn split_unix(line: &str) -> Result<Vec<String>, String> {
+    shell_words::split(line).map_err(|e| format!("Invalid arguments: {}", e))
+}
+
+/// Quote `args` for a POSIX shell
+pub fn join_unix(args: &[String]) -> String {
+    shell_words::join(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    /// Arguments that are awkward to quote on at least one platform
+    const TRICKY: &[&[&str]] = &[
+        &[],
+        &[""],
+        &["plain"],
+        &["two words", "three more words"],
+        &["C:\\Program Files\\App\\"],
+        &["C:\\Users\\O'Brien\\file.txt"],
+        &["trailing\\\\", "\\\\server\\share"],
+        &["say \"hi\"", "\"", "\\\"", "\\\\\""],
+        &["tab\there", "new\nline", "  padded  "],
+        &["$HOME", "%PATH%", "a;b|c&d", "*.txt", "~"],
+        &["naïve", "日本語", "emoji 🎉"],
+        &["--flag=value with spaces", "-x", "--"],
+    ];
+
+    #[test]
+    fn windows_round_trip() {
+        for args in TRICKY {
+            let args = strings(args);
+            assert_eq!(split_windows(&join_windows(&args)), args, "via {:?}", join_windows(&args));
+        }
+    }
+
+    #[test]
+    fn unix_round_trip() {
+        for args in TRICKY {
+            let args = strings(args);
+            assert_eq!(split_unix(&join_unix(&args)).unwrap(), args, "via {:?}", join_unix(&args));
+        }
+    }
+
+    #[test]
+    fn windows_splitting_rules() {
+        let cases: &[(&str, &[&str])] = &[
+            ("a b\tc", &["a", "b", "c"]),
+            ("  a   b  ", &["a", "b"]),
+            (r#""a b" c"#, &["a b", "c"]),
+            (r#"a"b c"d"#, &["ab cd"]),
+            (r#""" x"#, &["", "x"]),
+            (r#"C:\dir\ "C:\Program Files\""#, &[r"C:\dir\", r#"C:\Program Files""#]),
+            (r#"a\\b"#, &[r"a\\b"]),
+            (r#"a\\"b c" d"#, &[r"a\b c", "d"]),
+            (r#"a\"b"#, &[r#"a"b"#]),
+            (r#"a\\\"b"#, &[r#"a\"b"#]),
+            (r#""a""b""#, &[r#"a"b"#]),
+            ("O'Brien 'not quoted'", &["O'Brien", "'not", "quoted'"]),
+            (r#""unterminated"#, &["unterminated"]),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(split_windows(line), strings(expected), "splitting {:?}", line);
+        }
+    }
+
+    #[test]
+    fn windows_quoting() {
+        assert_eq!(join_windows(&strings(&["a", "b c", ""])), r#"a "b c" """#);
+        assert_eq!(join_windows(&strings(&[r"C:\dir\"])), r"C:\dir\");
+        assert_eq!(join_windows(&strings(&[r"C:\my dir\"])), r#""C:\my dir\\""#);
+        assert_eq!(join_windows(&strings(&[r#"a"b"#])), r#"a\"b"#);
+        assert_eq!(join_windows(&strings(&[r#"a\"b"#])), r#"a\\\"b"#);
+    }
+
+    /// Parse `line` the way cmd parses the command after `/S /C "..."`: expand set
+    /// variables, then remove escaping carets and split off further commands at unescaped
+    /// `&` and `|`. Returns the argv each command sees.
+    fn cmd_commands(line: &str) -> Vec<Vec<String>> {
+        let lookup = |name: &str| (name == "PATH").then_some("C:\\Windows");
+        let mut expanded = String::new();
+        let mut rest = line;
+        while let Some(start) = rest.find('%') {
+            expanded.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+            match after.find('%').and_then(|end| Some((end, lookup(&after[..end])?))) {
+                Some((end, value)) => {
+                    expanded.push_str(value);
+                    rest = &after[end + 1..];
+                }
+                None => {
+                    expanded.push('%');
+                    rest = after;
+                }
+            }
+        }
+        expanded.push_str(rest);
+
+        let mut commands = vec![String::new()];
+        let mut in_quotes = false;
+        let mut chars = expanded.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => {
+                    in_quotes = !in_quotes;
+                    commands.last_mut().unwrap().push(c);
+                }
+                '^' if !in_quotes => commands.last_mut().unwrap().extend(chars.next()),
+                '&' | '|' if !in_quotes => commands.push(String::new()),
+                '<' | '>' if !in_quotes => panic!("redirection in {:?}", line),
+                c => commands.last_mut().unwrap().push(c),
+            }
+        }
+        commands.iter().map(|command| split_windows(command)).collect()
+    }
+
+    #[test]
+    fn cmd_runs_one_command_with_the_arguments_given() {
+        let cases: &[&[&str]] = &[
+            &[r"C:\Tools\R&D\run.bat", "http://x/?a=1&b=2"],
+            &[r"C:\My Tools\run.bat", "a|b", "<in>", "(x)", "50%", "%PATH%", "!x!", "^", "a^&b"],
+            &[r"C:\run.bat", "say \"hi & bye\"", "", "trailing\\", "\"&calc&\""],
+            &["start", "", "https://example.com/?q=a&r=%20b"],
+        ];
+        for args in cases {
+            let args = strings(args);
+            let line = join_cmd(&args);
+            assert_eq!(cmd_commands(&line), [args], "via {:?}", line);
+        }
+    }
+
+    #[test]
+    fn config_args_are_a_string_or_a_list() {
+        use crate::config::CommandArgs;
+
+        let line: CommandArgs = serde_json::from_str(r#""--name 'two words'""#).unwrap();
+        assert_eq!(line, CommandArgs::Line("--name 'two words'".to_string()));
+        let list: CommandArgs = serde_json::from_str(r#"["--name", "two words"]"#).unwrap();
+        assert_eq!(list.to_argv().unwrap(), strings(&["--name", "two words"]));
+        assert_eq!(serde_json::to_string(&list).unwrap(), r#"["--name","two words"]"#);
+    }
+
+    #[test]
+    fn unix_splitting_rules() {
+        let cases: &[(&str, &[&str])] = &[
+            ("a b\tc", &["a", "b", "c"]),
+            ("'single quoted' \"double quoted\"", &["single quoted", "double quoted"]),
+            (r#"escaped\ space "in \"quotes\"""#, &["escaped space", r#"in "quotes""#]),
+            ("'$HOME' $HOME", &["$HOME", "$HOME"]),
+            ("''", &[""]),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(split_unix(line).unwrap(), strings(expected), "splitting {:?}", line);
+        }
+        assert!(split_unix("'unterminated").is_err());
+    }
+}
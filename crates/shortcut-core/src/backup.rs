@@ -0,0 +1,185 @@
+//! Crash-safe config writes and the rotating backups kept alongside them.
+//!
+//! Every save first copies the current config.json into `backups/` next to it,
+//! then replaces the file through a temp file + rename so a crash can never
+//! leave a half-written config behind.
+
+use serde::Serialize;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::config::{get_config_path, parse_config, AppConfig};
+
+/// How many previous versions of config.json are kept
+pub const MAX_BACKUPS: usize = 10;
+
+const BACKUP_PREFIX: &str = "config-";
+const BACKUP_SUFFIX: &str = ".json";
+const STAMP_FORMAT: &str = "%Y%m%d-%H%M%S%3f";
+
+/// A saved copy of an earlier config.json
+#[derive(Serialize, Clone, Debug)]
+pub struct BackupInfo {
+    // File name inside the backups directory, used to restore it
+    pub name: String,
+    // Local time the backup was taken, e.g. "2024-05-01 14:03:22"
+    pub created: String,
+    pub size: u64,
+}
+
+/// Directory holding the rotating backups
+pub fn backup_dir() -> PathBuf {
+    let mut dir = get_config_path();
+    dir.pop();
+    dir.push("backups");
+    dir
+}
+
+/// Write `contents` to `path` through a synced temp file in the same directory
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let dir = path.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let mut temp = tempfile::Builder::new()
+        .prefix(".config-")
+        .suffix(".tmp")
+        .tempfile_in(dir)
+        .map_err(|e| format!("Failed to create temp file: {}", e))?;
+    // Keep the permissions of the file being replaced rather than the temp file's 0600
+    if let Ok(meta) = fs::metadata(path) {
+        let _ = temp.as_file().set_permissions(meta.permissions());
+    }
+    temp.write_all(contents)
+        .map_err(|e| format!("Failed to write config: {}", e))?;
+    temp.as_file()
+        .sync_all()
+        .map_err(|e| format!("Failed to flush config to disk: {}", e))?;
+    temp.persist(path)
+        .map_err(|e| format!("Failed to replace config: {}", e.error))?;
+    Ok(())
+}
+
+/// Copy the current config file into the backups directory and prune old copies
+pub fn backup_current() -> Result<(), String> {
+    let path = get_config_path();
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let dir = backup_dir();
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create backup directory: {}", e))?;
+    let stamp = chrono::Local::now().format(STAMP_FORMAT);
+    let target = dir.join(format!("{}{}{}", BACKUP_PREFIX, stamp, BACKUP_SUFFIX));
+    fs::copy(&path, &target)
+        .map_err(|e| format!("Failed to back up config: {}", e))?;
+
+    prune(&dir);
+    Ok(())
+}
+
+/// Delete all but the newest MAX_BACKUPS backups
+fn prune(dir: &Path) {
+    let names = backup_names(dir);
+    if names.len() > MAX_BACKUPS {
+        for name in &names[MAX_BACKUPS..] {
+            let _ = fs::remove_file(dir.join(name));
+        }
+    }
+}
+
+/// Backup file names, newest first (the timestamp in the name sorts chronologically)
+fn backup_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter_map(|e| e.file_name().into_string().ok())
+                .filter(|n| n.starts_with(BACKUP_PREFIX) && n.ends_with(BACKUP_SUFFIX))
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort_unstable_by(|a, b| b.cmp(a));
+    names
+}
+
+/// List available backups, newest first
+pub fn list_backups() -> Result<Vec<BackupInfo>, String> {
+    let dir = backup_dir();
+    let backups = backup_names(&dir)
+        .into_iter()
+        .filter_map(|name| {
+            let meta = fs::metadata(dir.join(&name)).ok()?;
+            // The name carries the time of the backup; file times differ between platforms' copy
+            let stamp = &name[BACKUP_PREFIX.len()..name.len() - BACKUP_SUFFIX.len()];
+            let created = chrono::NaiveDateTime::parse_from_str(stamp, STAMP_FORMAT)
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default();
+            Some(BackupInfo { name, created, size: meta.len() })
+        })
+        .collect();
+    Ok(backups)
+}
+
+/// Read and validate the backup called `name`, see [`ConfigStore::restore_backup`](crate::store::ConfigStore::restore_backup)
+pub fn read_backup(name: &str) -> Result<AppConfig, String> {
+    // Only plain file names from list_backups are accepted
+    if name.contains(['/', '\\']) || !name.starts_with(BACKUP_PREFIX) || !name.ends_with(BACKUP_SUFFIX) {
+        return Err(format!("Invalid backup name: {}", name));
+    }
+
+    let path = backup_dir().join(name);
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read backup {}: {}", name, e))?;
+    let (mut config, _) = parse_config(&content).map_err(|e| format!("Backup {} is invalid: {}", name, e))?;
+    config.ensure_ids();
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{lock_test_config, save_config};
+
+    #[test]
+    fn prune_keeps_the_newest_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        for day in 1..=MAX_BACKUPS + 3 {
+            fs::write(dir.path().join(format!("{}202401{:02}-000000000{}", BACKUP_PREFIX, day, BACKUP_SUFFIX)), "{}").unwrap();
+        }
+        fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+        prune(dir.path());
+        let names = backup_names(dir.path());
+        assert_eq!(names.len(), MAX_BACKUPS);
+        assert_eq!(names.first().unwrap(), "config-20240113-000000000.json");
+        assert_eq!(names.last().unwrap(), "config-20240104-000000000.json");
+        assert!(dir.path().join("notes.txt").exists());
+    }
+
+    #[test]
+    fn every_save_backs_up_the_previous_config() {
+        crate::set_config_path(tempfile::tempdir().unwrap().keep().join("config.json"));
+        let _lock = lock_test_config();
+        for version in 0..MAX_BACKUPS + 2 {
+            let config = AppConfig { terminal: Some(version.to_string()), ..Default::default() };
+            save_config(&config).unwrap();
+            // Backups are named by the millisecond
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+
+        let backups = list_backups().unwrap();
+        assert_eq!(backups.len(), MAX_BACKUPS);
+        assert!(!backups[0].created.is_empty());
+        // The newest backup holds the config from before the last save
+        let newest = read_backup(&backups[0].name).unwrap();
+        assert_eq!(newest.terminal.as_deref(), Some(MAX_BACKUPS.to_string().as_str()));
+    }
+
+    #[test]
+    fn only_backup_names_can_be_read() {
+        for name in ["../config.json", "config-1/../../x.json", "config-1\\..\\x.json", "other.json", "config-1.txt"] {
+            assert_eq!(read_backup(name).unwrap_err(), format!("Invalid backup name: {}", name));
+        }
+        assert!(read_backup("config-19700101-000000000.json").unwrap_err().starts_with("Failed to read backup"));
+    }
+}
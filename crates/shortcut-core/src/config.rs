@@ -0,0 +1,797 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use crate::args;
+use crate::backup;
+use crate::migrate::{self, CONFIG_VERSION};
+
+/// Shortcut types supported by the application
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ShortcutType {
+    #[default]
+    App,      // Launch an application
+    Url,      // Open URL in default browser
+    File,     // Open file with default application
+    Folder,   // Open folder in explorer
+    Script,   // Run a script file (bat, ps1, sh)
+    Shell,    // Run shell command (can be multi-line)
+    Sequence, // Run other shortcuts one after another
+}
+
+impl fmt::Display for ShortcutType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ShortcutType::App => "app",
+            ShortcutType::Url => "url",
+            ShortcutType::File => "file",
+            ShortcutType::Folder => "folder",
+            ShortcutType::Script => "script",
+            ShortcutType::Shell => "shell",
+            ShortcutType::Sequence => "sequence",
+        };
+        f.pad(name)
+    }
+}
+
+impl FromStr for ShortcutType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "app" => Ok(ShortcutType::App),
+            "url" => Ok(ShortcutType::Url),
+            "file" => Ok(ShortcutType::File),
+            "folder" => Ok(ShortcutType::Folder),
+            "script" => Ok(ShortcutType::Script),
+            "shell" => Ok(ShortcutType::Shell),
+            "sequence" => Ok(ShortcutType::Sequence),
+            other => Err(format!("Unknown shortcut type: {}", other)),
+        }
+    }
+}
+
+/// Arguments of an app or script shortcut
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum CommandArgs {
+    /// A command line, split with the platform's quoting rules (see [`crate::args`])
+    Line(String),
+    /// Arguments passed exactly as given
+    List(Vec<String>),
+}
+
+impl CommandArgs {
+    /// The arguments the program receives
+    pub fn to_argv(&self) -> Result<Vec<String>, String> {
+        match self {
+            CommandArgs::Line(line) => args::split_args(line),
+            CommandArgs::List(list) => Ok(list.clone()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self {
+            CommandArgs::Line(line) => line.trim().is_empty(),
+            CommandArgs::List(list) => list.is_empty(),
+        }
+    }
+}
+
+/// One step of a sequence shortcut: another shortcut by ID, or one defined in place
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SequenceStep {
+    // ID of the shortcut to run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
+    // Shortcut defined inline, run instead of looking up `id`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shortcut: Option<Box<Shortcut>>,
+
+    // Wait for the step's process to exit before going on; a non-zero exit code is a failure
+    #[serde(default)]
+    pub wait: bool,
+
+    // Pause this many milliseconds before starting the step
+    #[serde(default)]
+    pub delay_ms: u64,
+
+    // Go on with the next steps when this one fails
+    #[serde(default)]
+    pub continue_on_failure: bool,
+
+    // Run the step, with its delay and wait, alongside the steps after it instead of before them
+    #[serde(default)]
+    pub parallel: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Shortcut {
+    // Stable identifier; configs written before IDs existed get one on load
+    #[serde(default)]
+    pub id: String,
+
+    pub name: String,
+
+    #[serde(default)]
+    #[serde(rename = "type")]
+    pub shortcut_type: ShortcutType,
+
+    // For app/script: executable path; for url: the URL; for file/folder: path
+    #[serde(default)]
+    pub command: String,
+
+    // For shell type: the actual script content (multi-line supported)
+    pub script: Option<String>,
+
+    // Command-line arguments, as one string or a list of already split arguments
+    pub args: Option<CommandArgs>,
+
+    // Working directory
+    pub working_dir: Option<String>,
+
+    // Extra environment variables; values may reference other variables
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+
+    // dotenv file loaded before `env`; relative paths are resolved against the config directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_file: Option<String>,
+
+    // Optional description shown in UI
+    pub description: Option<String>,
+
+    // For shell/script: run hidden (no console window)
+    #[serde(default)]
+    pub hidden: bool,
+
+    // For shell/script: shell to use (cmd, powershell, pwsh, sh, bash, zsh, fish, python, node
+    // or interpreter)
+    pub shell: Option<String>,
+
+    // For shell "interpreter": the program that runs the script
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interpreter: Option<String>,
+
+    // Arguments for `interpreter`; "{script}" stands for the script file, without it the
+    // script is passed on stdin
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interpreter_args: Option<CommandArgs>,
+
+    // Run as administrator
+    #[serde(default)]
+    pub admin: bool,
+
+    // For shell/script run visibly: wait for Enter before closing the window
+    #[serde(default)]
+    pub keep_open: bool,
+
+    // For shell/script: post a desktop notification when the run ends
+    #[serde(default)]
+    pub notify_on_exit: bool,
+
+    // Stop the run (with everything it started) after this many seconds
+    pub timeout_secs: Option<u64>,
+
+    // For sequence: the steps, run in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<SequenceStep>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AppConfig {
+    // Schema version, see migrate::CONFIG_VERSION
+    #[serde(default)]
+    pub version: u32,
+
+    // Terminal emulator visible scripts open in on Linux, e.g. "kitty" or "foot -e";
+    // detected when not set. A command line must not return before the script exits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub terminal: Option<String>,
+
+    pub shortcuts: Vec<Shortcut>,
+}
+
+impl AppConfig {
+    /// Give every shortcut without a unique ID a fresh one; returns whether anything changed
+    pub fn ensure_ids(&mut self) -> bool {
+        let mut seen = std::collections::HashSet::new();
+        let mut changed = false;
+        for shortcut in &mut self.shortcuts {
+            if shortcut.id.is_empty() || !seen.insert(shortcut.id.clone()) {
+                shortcut.id = new_shortcut_id();
+                seen.insert(shortcut.id.clone());
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Position of the shortcut with the given ID
+    pub fn position(&self, id: &str) -> Option<usize> {
+        self.shortcuts.iter().position(|s| s.id == id)
+    }
+
+    /// Shortcut with the given ID
+    pub fn find(&self, id: &str) -> Option<&Shortcut> {
+        self.shortcuts.iter().find(|s| s.id == id)
+    }
+}
+
+/// Sample shortcuts for a first run, using programs the platform ships with
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            terminal: None,
+            shortcuts: vec![
+                Shortcut {
+                    id: new_shortcut_id(),
+                    name: if cfg!(windows) { "Notepad" } else { "Text Editor" }.to_string(),
+                    shortcut_type: ShortcutType::App,
+                    command: if cfg!(windows) { "notepad.exe" } else { "gnome-text-editor" }.to_string(),
+                    script: None,
+                    args: None,
+                    working_dir: None,
+                    description: Some("Text editor".to_string()),
+                    hidden: false,
+                    shell: None,
+                    admin: false,
+                    ..Default::default()
+                },
+                Shortcut {
+                    id: new_shortcut_id(),
+                    name: "Calculator".to_string(),
+                    shortcut_type: ShortcutType::App,
+                    command: if cfg!(windows) { "calc.exe" } else { "gnome-calculator" }.to_string(),
+                    script: None,
+                    args: None,
+                    working_dir: None,
+                    description: None,
+                    hidden: false,
+                    shell: None,
+                    admin: false,
+                    ..Default::default()
+                },
+                Shortcut {
+                    id: new_shortcut_id(),
+                    name: "Google".to_string(),
+                    shortcut_type: ShortcutType::Url,
+                    command: "https://www.google.com".to_string(),
+                    script: None,
+                    args: None,
+                    working_dir: None,
+                    description: Some("Search engine".to_string()),
+                    hidden: false,
+                    shell: None,
+                    admin: false,
+                    ..Default::default()
+                },
+                Shortcut {
+                    id: new_shortcut_id(),
+                    name: "Documents".to_string(),
+                    shortcut_type: ShortcutType::Folder,
+                    command: if cfg!(windows) { "%USERPROFILE%\\Documents" } else { "~/Documents" }.to_string(),
+                    script: None,
+                    args: None,
+                    working_dir: None,
+                    description: None,
+                    hidden: false,
+                    shell: None,
+                    admin: false,
+                    ..Default::default()
+                },
+                Shortcut {
+                    id: new_shortcut_id(),
+                    name: "System Info".to_string(),
+                    shortcut_type: ShortcutType::Shell,
+                    command: String::new(),
+                    script: Some(if cfg!(windows) {
+                        "systeminfo | findstr /B /C:\"OS Name\" /C:\"OS Version\"\npause".to_string()
+                    } else {
+                        "uname -sr\ngrep -E '^(NAME|VERSION)=' /etc/os-release".to_string()
+                    }),
+                    args: None,
+                    working_dir: None,
+                    description: Some("Show OS info".to_string()),
+                    hidden: false,
+                    shell: Some(if cfg!(windows) { "cmd" } else { "sh" }.to_string()),
+                    admin: false,
+                    // Takes the place of `pause` in the terminal window
+                    keep_open: !cfg!(windows),
+                    ..Default::default()
+                },
+            ],
+        }
+    }
+}
+
+/// Generate a new random shortcut ID
+pub fn new_shortcut_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Environment variable pointing at an alternative config file
+pub const CONFIG_ENV_VAR: &str = "SHORTCUTS_CONFIG";
+
+/// A file with this name next to the executable switches to portable mode
+pub const PORTABLE_MARKER: &str = "portable";
+
+static CONFIG_PATH_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Held by tests that write config.json, since the config path is shared by every test
+#[cfg(test)]
+pub(crate) fn lock_test_config() -> std::sync::MutexGuard<'static, ()> {
+    static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Use `path` as the config file for the rest of the process (the `--config` flag).
+///
+/// Takes precedence over everything else; only the first call has an effect.
+pub fn set_config_path(path: impl Into<PathBuf>) {
+    let path = path.into();
+    let path = std::path::absolute(&path).unwrap_or(path);
+    let _ = CONFIG_PATH_OVERRIDE.set(path);
+}
+
+/// Locate config.json, in order of precedence:
+///
+/// 1. the path given to [`set_config_path`] (`--config`)
+/// 2. the `SHORTCUTS_CONFIG` environment variable
+/// 3. `config.json` next to the executable when a `portable` marker file sits there
+/// 4. `%APPDATA%\shortcuts` on Windows, `$XDG_CONFIG_HOME/shortcuts` (or `~/.config/shortcuts`) elsewhere
+pub fn get_config_path() -> PathBuf {
+    let path = CONFIG_PATH_OVERRIDE
+        .get()
+        .cloned()
+        .or_else(|| {
+            // Made absolute like --config, so relative paths in the config keep one base
+            let path = PathBuf::from(std::env::var_os(CONFIG_ENV_VAR).filter(|p| !p.is_empty())?);
+            Some(std::path::absolute(&path).unwrap_or(path))
+        })
+        .or_else(portable_config_path)
+        .unwrap_or_else(|| default_config_dir().join("config.json"));
+
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        if !dir.exists() {
+            let _ = fs::create_dir_all(dir);
+        }
+    }
+    path
+}
+
+/// Config path for portable installs, e.g. on a USB stick
+fn portable_config_path() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    let dir = exe.parent()?;
+    dir.join(PORTABLE_MARKER).exists().then(|| dir.join("config.json"))
+}
+
+#[cfg(target_os = "windows")]
+fn default_config_dir() -> PathBuf {
+    let app_data = std::env::var("APPDATA").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(app_data).join("shortcuts")
+}
+
+#[cfg(not(target_os = "windows"))]
+fn default_config_dir() -> PathBuf {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|p| !p.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .unwrap_or_else(|| PathBuf::from("."));
+    config_home.join("shortcuts")
+}
+
+/// Why the config file could not be loaded
+#[derive(Serialize, Clone, Debug)]
+pub struct ConfigError {
+    pub path: String,
+    pub message: String,
+    // 1-based position of a JSON error, when known
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+impl ConfigError {
+    fn new(message: impl Into<String>) -> Self {
+        Self { path: String::new(), message: message.into(), line: None, column: None }
+    }
+
+    fn at(mut self, path: &Path) -> Self {
+        self.path = path.display().to_string();
+        self
+    }
+}
+
+impl From<String> for ConfigError {
+    fn from(message: String) -> Self {
+        Self::new(message)
+    }
+}
+
+impl From<serde_json::Error> for ConfigError {
+    fn from(e: serde_json::Error) -> Self {
+        // serde_json reports line 0 when the error has no position (e.g. from_value)
+        let located = e.line() > 0;
+        let message = e.to_string();
+        // Drop the " at line X column Y" suffix, the position is reported separately
+        let message = match message.rfind(" at line ") {
+            Some(pos) if located => message[..pos].to_string(),
+            _ => message,
+        };
+        Self {
+            path: String::new(),
+            message,
+            line: located.then(|| e.line()),
+            column: located.then(|| e.column()),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.path.is_empty() {
+            write!(f, "{}:", self.path)?;
+        }
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, "{}:{}:", line, column)?;
+        }
+        if !self.path.is_empty() || self.line.is_some() {
+            f.write_str(" ")?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<ConfigError> for String {
+    fn from(e: ConfigError) -> Self {
+        e.to_string()
+    }
+}
+
+/// Parse config JSON, upgrading older layouts; also returns the version the content started at
+pub fn parse_config(content: &str) -> Result<(AppConfig, u32), ConfigError> {
+    let mut value: serde_json::Value = serde_json::from_str(content)?;
+    let from_version = migrate::migrate(&mut value).map_err(ConfigError::new)?;
+    let config: AppConfig = if from_version == CONFIG_VERSION {
+        // Deserialize the text again so type errors carry a line and column
+        serde_json::from_str(content)?
+    } else {
+        serde_json::from_value(value)?
+    };
+    Ok((config, from_version))
+}
+
+/// Copy the file at `path` aside before it is rewritten by a migration
+fn backup_before_migration(path: &Path, from_version: u32) -> Result<PathBuf, String> {
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(format!(".v{}-{}.bak", from_version, stamp));
+    let backup = PathBuf::from(backup);
+    fs::copy(path, &backup)
+        .map_err(|e| format!("Failed to back up config before migration: {}", e))?;
+    Ok(backup)
+}
+
+/// Load the config, creating the default one if the file does not exist yet.
+///
+/// A file that cannot be read or parsed is left untouched and reported as an error,
+/// so a typo never costs the user their shortcuts. This may write the file (defaults,
+/// migrations), so use [`ConfigStore`](crate::store::ConfigStore) when other writers may be running.
+pub fn load_config() -> Result<AppConfig, ConfigError> {
+    let path = get_config_path();
+    if !path.exists() {
+        let config = AppConfig::default();
+        if let Ok(json) = serde_json::to_string_pretty(&config) {
+            let _ = backup::write_atomic(&path, json.as_bytes());
+        }
+        return Ok(config);
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| ConfigError::new(format!("Failed to read config: {}", e)).at(&path))?;
+    let (mut config, from_version) = parse_config(&content).map_err(|e| e.at(&path))?;
+
+    let ids_changed = config.ensure_ids();
+    if from_version < CONFIG_VERSION {
+        // Only rewrite an old file once its original content is safely copied aside
+        if backup_before_migration(&path, from_version).is_ok() {
+            let _ = save_config(&config);
+        }
+    } else if ids_changed {
+        // Persist IDs generated for shortcuts added by hand so they stay stable
+        let _ = save_config(&config);
+    }
+    Ok(config)
+}
+
+/// Atomically replace the config file, keeping the previous version as a backup.
+///
+/// Does not lock; go through [`ConfigStore`](crate::store::ConfigStore) unless the lock is already held.
+pub fn save_config(config: &AppConfig) -> Result<(), String> {
+    let path = get_config_path();
+    let json = serde_json::to_string_pretty(config)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    // A failed backup must not block saving the user's change
+    let _ = backup::backup_current();
+    backup::write_atomic(&path, json.as_bytes())
+}
+
+/// Read and validate a config stored at `path`, e.g. for importing it
+pub fn read_config_file(path: &Path) -> Result<AppConfig, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let (mut config, _) = parse_config(&content).map_err(|e| e.at(path))?;
+    config.ensure_ids();
+    Ok(config)
+}
+
+/// Expand environment variables in a path or URL.
+///
+/// Understands `%VAR%`, `$VAR`, `${VAR}`, `${VAR:-default}` and a leading `~` for the
+/// home directory. `%%` and `$$` stand for a literal `%` and `$`. `%` only starts a
+/// variable when followed by a name, so URL escapes like `%20` are left alone, as
+/// are variables that are not set.
+pub fn expand_env_vars(input: &str) -> String {
+    expand_env_vars_with(input, |name| std::env::var(name).ok())
+}
+
+/// Like [`expand_env_vars`], looking variables up with `lookup` instead of the process environment
+pub fn expand_env_vars_with(input: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    expand_env_vars_checked(input, lookup).0
+}
+
+/// Like [`expand_env_vars_with`], also returning the names of variables that were not set
+pub fn expand_env_vars_checked(input: &str, lookup: impl Fn(&str) -> Option<String>) -> (String, Vec<String>) {
+    let mut unresolved = Vec::new();
+    let result = expand_into(input, &lookup, &mut unresolved);
+    (result, unresolved)
+}
+
+type Lookup<'a> = &'a dyn Fn(&str) -> Option<String>;
+
+fn expand_into(input: &str, lookup: Lookup, unresolved: &mut Vec<String>) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut rest = input;
+
+    if let Some(after) = rest.strip_prefix('~') {
+        if after.is_empty() || after.starts_with(['/', '\\']) {
+            if let Some(home) = lookup("HOME").or_else(|| lookup("USERPROFILE")) {
+                result.push_str(&home);
+                rest = after;
+            }
+        }
+    }
+
+    while let Some(pos) = rest.find(['%', '$']) {
+        result.push_str(&rest[..pos]);
+        let consumed = if rest[pos..].starts_with('%') {
+            expand_percent(&rest[pos..], lookup, &mut result, unresolved)
+        } else {
+            expand_dollar(&rest[pos..], lookup, &mut result, unresolved)
+        };
+        rest = &rest[pos + consumed..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Length of the variable name at the start of `input`, 0 if there is none
+fn name_len(input: &str, extra: &[char]) -> usize {
+    let mut chars = input.char_indices();
+    match chars.next() {
+        Some((_, c)) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return 0,
+    }
+    chars
+        .find(|&(_, c)| !(c.is_ascii_alphanumeric() || c == '_' || extra.contains(&c)))
+        .map_or(input.len(), |(i, _)| i)
+}
+
+/// Expand the `%...` at the start of `input` onto `out`, returning how much of it was used
+fn expand_percent(
+    input: &str,
+    lookup: Lookup,
+    out: &mut String,
+    unresolved: &mut Vec<String>,
+) -> usize {
+    let body = &input[1..];
+    if body.starts_with('%') {
+        out.push('%');
+        return 2;
+    }
+    // Windows names such as ProgramFiles(x86) contain parentheses
+    let len = name_len(body, &['(', ')']);
+    if len == 0 || !body[len..].starts_with('%') {
+        out.push('%');
+        return 1;
+    }
+    let name = &body[..len];
+    match lookup(name) {
+        Some(value) => out.push_str(&value),
+        None => {
+            unresolved.push(name.to_string());
+            out.push_str(&input[..len + 2]);
+        }
+    }
+    len + 2
+}
+
+/// Expand the `$...` at the start of `input` onto `out`, returning how much of it was used
+fn expand_dollar(
+    input: &str,
+    lookup: Lookup,
+    out: &mut String,
+    unresolved: &mut Vec<String>,
+) -> usize {
+    let body = &input[1..];
+    if body.starts_with('$') {
+        out.push('$');
+        return 2;
+    }
+
+    if let Some(braced) = body.strip_prefix('{') {
+        // Find the matching brace; a default may itself contain ${...}
+        let mut depth = 1;
+        let close = braced.char_indices().find_map(|(i, c)| {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            (depth == 0).then_some(i)
+        });
+        let Some(close) = close else {
+            out.push('$');
+            return 1;
+        };
+        let inner = &braced[..close];
+        let len = name_len(inner, &[]);
+        let default = inner[len..].strip_prefix(":-");
+        if len == 0 || (len < inner.len() && default.is_none()) {
+            out.push('$');
+            return 1;
+        }
+        let name = &inner[..len];
+        // Like the shell, an empty value also falls back to the default
+        match (lookup(name).filter(|v| default.is_none() || !v.is_empty()), default) {
+            (Some(value), _) => out.push_str(&value),
+            (None, Some(default)) => {
+                out.push_str(&expand_into(default, lookup, unresolved));
+            }
+            (None, None) => {
+                unresolved.push(name.to_string());
+                out.push_str(&input[..close + 3]);
+            }
+        }
+        return close + 3;
+    }
+
+    let len = name_len(body, &[]);
+    if len == 0 {
+        out.push('$');
+        return 1;
+    }
+    let name = &body[..len];
+    match lookup(name) {
+        Some(value) => out.push_str(&value),
+        None => {
+            unresolved.push(name.to_string());
+            out.push_str(&input[..len + 1]);
+        }
+    }
+    len + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        let value = match name {
+            "HOME" => "/home/me",
+            "SET" => "set",
+            "EMPTY" => "",
+            "ProgramFiles(x86)" => r"C:\Program Files (x86)",
+            _ => return None,
+        };
+        Some(value.to_string())
+    }
+
+    fn expand(input: &str) -> String {
+        expand_env_vars_with(input, lookup)
+    }
+
+    #[test]
+    fn expands_every_syntax() {
+        let cases: &[(&str, &str)] = &[
+            ("%SET%", "set"),
+            ("$SET", "set"),
+            ("${SET}", "set"),
+            ("a/$SET/b", "a/set/b"),
+            ("$SET.txt", "set.txt"),
+            ("%SET%%SET%", "setset"),
+            (r"%ProgramFiles(x86)%\App", r"C:\Program Files (x86)\App"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(expand(input), *expected, "expanding {:?}", input);
+        }
+    }
+
+    #[test]
+    fn leaves_url_escapes_and_lone_signs_alone() {
+        let cases = [
+            "https://example.com/a%20b?q=%2F%3d",
+            "100%",
+            "50% off, 20% more",
+            "%1 %*",
+            "$",
+            "$1 $@",
+            "cost: $ 5",
+            "${",
+            "${SET",
+            "${SET:x}",
+            "${}",
+        ];
+        for input in cases {
+            assert_eq!(expand(input), input, "expanding {:?}", input);
+        }
+    }
+
+    #[test]
+    fn doubled_signs_are_literal() {
+        assert_eq!(expand("100%%"), "100%");
+        assert_eq!(expand("%%SET%%"), "%SET%");
+        assert_eq!(expand("$$SET"), "$SET");
+        assert_eq!(expand("$${SET}"), "${SET}");
+    }
+
+    #[test]
+    fn defaults() {
+        assert_eq!(expand("${UNSET:-fallback}"), "fallback");
+        assert_eq!(expand("${SET:-fallback}"), "set");
+        assert_eq!(expand("${UNSET:-}"), "");
+        assert_eq!(expand("${UNSET:-${SET}}"), "set");
+        assert_eq!(expand("${UNSET:-${ALSO_UNSET:-deep}}/x"), "deep/x");
+        assert_eq!(expand("${UNSET:-a}{b}"), "a{b}");
+        // Like the shell, an empty value takes the default, and stays empty without one
+        assert_eq!(expand("${EMPTY:-fallback}"), "fallback");
+        assert_eq!(expand("[${EMPTY}]"), "[]");
+        assert_eq!(expand("[$EMPTY]"), "[]");
+    }
+
+    #[test]
+    fn home_directory() {
+        assert_eq!(expand("~"), "/home/me");
+        assert_eq!(expand("~/x"), "/home/me/x");
+        assert_eq!(expand(r"~\x"), r"/home/me\x");
+        assert_eq!(expand("~user/x"), "~user/x");
+        assert_eq!(expand("a/~"), "a/~");
+        assert_eq!(expand_env_vars_with("~/x", |_| None), "~/x");
+    }
+
+    #[test]
+    fn reports_unresolved_names() {
+        let (expanded, unresolved) = expand_env_vars_checked("%A%/$B/${C}/${D:-ok}/$SET/%A%", lookup);
+        assert_eq!(expanded, "%A%/$B/${C}/ok/set/%A%");
+        assert_eq!(unresolved, ["A", "B", "C", "A"]);
+
+        let (_, unresolved) = expand_env_vars_checked("${UNSET:-${NESTED}}", lookup);
+        assert_eq!(unresolved, ["NESTED"]);
+        let (_, unresolved) = expand_env_vars_checked("100% $5 %20", lookup);
+        assert!(unresolved.is_empty());
+    }
+}
@@ -0,0 +1,173 @@
+//! Per-shortcut environment variables.
+//!
+//! A shortcut can load variables from a dotenv file (`env_file`) and set its own
+//! (`env`), which win over the file. Values are expanded with
+//! [`expand_env_vars_with`], seeing the file's variables and then the inherited
+//! environment.
+
+use std::path::PathBuf;
+
+use crate::config::{expand_env_vars_checked, expand_env_vars_with, get_config_path, Shortcut, ShortcutType};
+use crate::launcher::LaunchError;
+
+/// Variables a launch of `shortcut` sets on top of the inherited environment,
+/// in the order they are applied
+pub fn resolve_env(shortcut: &Shortcut) -> Result<Vec<(String, String)>, LaunchError> {
+    let from_file = match &shortcut.env_file {
+        Some(file) if !file.trim().is_empty() => read_env_file(file)?,
+        _ => Vec::new(),
+    };
+    Ok(with_env(from_file, shortcut))
+}
+
+/// `from_file` with the shortcut's own `env` applied on top
+fn with_env(from_file: Vec<(String, String)>, shortcut: &Shortcut) -> Vec<(String, String)> {
+    let mut vars = from_file.clone();
+    for (name, value) in &shortcut.env {
+        let value = expand_with(value, &from_file);
+        vars.retain(|(existing, _)| existing != name);
+        vars.push((name.clone(), value));
+    }
+    vars
+}
+
+/// Expand variables in `input`, seeing `vars` before the inherited environment
+pub fn expand_with(input: &str, vars: &[(String, String)]) -> String {
+    expand_env_vars_with(input, lookup_in(vars))
+}
+
+fn lookup_in(vars: &[(String, String)]) -> impl Fn(&str) -> Option<String> + '_ {
+    |name| {
+        vars.iter()
+            .rev()
+            .find(|(var, _)| var == name)
+            .map(|(_, value)| value.clone())
+            .or_else(|| std::env::var(name).ok())
+    }
+}
+
+/// Problems that do not stop `shortcut` from being saved but probably make it misbehave,
+/// such as variables that are not set and would be passed on literally
+pub fn warnings(shortcut: &Shortcut) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let from_file = match &shortcut.env_file {
+        Some(file) if !file.trim().is_empty() => {
+            check_vars(&mut warnings, "env_file", file, &[]);
+            read_env_file(file).unwrap_or_else(|e| {
+                warnings.push(e.to_string());
+                Vec::new()
+            })
+        }
+        _ => Vec::new(),
+    };
+    for (name, value) in &shortcut.env {
+        check_vars(&mut warnings, &format!("env {}", name), value, &from_file);
+    }
+    // Still checked against `env` when the file could not be read
+    let vars = with_env(from_file, shortcut);
+    if !matches!(shortcut.shortcut_type, ShortcutType::Shell | ShortcutType::Sequence) {
+        check_vars(&mut warnings, "command", &shortcut.command, &vars);
+    }
+    if let Some(dir) = &shortcut.working_dir {
+        check_vars(&mut warnings, "working_dir", dir, &vars);
+    }
+    if let Some(Err(e)) = shortcut.args.as_ref().map(|args| args.to_argv()) {
+        warnings.push(e);
+    }
+    warnings
+}
+
+/// Warn about every variable in `value` that neither `vars` nor the environment sets
+fn check_vars(warnings: &mut Vec<String>, field: &str, value: &str, vars: &[(String, String)]) {
+    let (_, mut unresolved) = expand_env_vars_checked(value, lookup_in(vars));
+    unresolved.dedup();
+    for name in unresolved {
+        warnings.push(format!("Variable {} in {} is not set", name, field));
+    }
+}
+
+/// Where `env_file` points, relative paths being taken from the config directory
+pub fn env_file_path(env_file: &str) -> PathBuf {
+    let path = PathBuf::from(crate::expand_env_vars(env_file));
+    if path.is_absolute() {
+        return path;
+    }
+    let mut dir = get_config_path();
+    dir.pop();
+    dir.join(path)
+}
+
+fn read_env_file(env_file: &str) -> Result<Vec<(String, String)>, LaunchError> {
+    let path = env_file_path(env_file);
+    let invalid = |e: dotenvy::Error| LaunchError::Invalid(format!("Invalid env file {}: {}", path.display(), e));
+    let iter = dotenvy::from_path_iter(&path).map_err(|e| {
+        if e.not_found() {
+            LaunchError::NotFound(path.display().to_string())
+        } else {
+            invalid(e)
+        }
+    })?;
+    iter.collect::<Result<Vec<_>, _>>().map_err(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn env(vars: &[(&str, &str)]) -> BTreeMap<String, String> {
+        vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn get<'a>(vars: &'a [(String, String)], name: &str) -> Option<&'a str> {
+        vars.iter().find(|(var, _)| var == name).map(|(_, value)| value.as_str())
+    }
+
+    #[test]
+    fn env_wins_over_env_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("vars.env");
+        std::fs::write(&file, "SHARED=file\nONLY_FILE=\"from file\"\n").unwrap();
+        let shortcut = Shortcut {
+            env_file: Some(file.to_string_lossy().into_owned()),
+            env: env(&[("SHARED", "env"), ("DERIVED", "${ONLY_FILE}!")]),
+            ..Default::default()
+        };
+        let vars = resolve_env(&shortcut).unwrap();
+        assert_eq!(get(&vars, "SHARED"), Some("env"));
+        assert_eq!(get(&vars, "ONLY_FILE"), Some("from file"));
+        assert_eq!(get(&vars, "DERIVED"), Some("from file!"));
+        assert_eq!(vars.iter().filter(|(var, _)| var == "SHARED").count(), 1);
+    }
+
+    #[test]
+    fn relative_env_file_is_taken_from_the_config_dir() {
+        crate::set_config_path(tempfile::tempdir().unwrap().keep().join("config.json"));
+        let dir = get_config_path().parent().unwrap().to_path_buf();
+        let name = format!("{}.env", uuid::Uuid::new_v4());
+        std::fs::write(dir.join(&name), "FROM_CONFIG_DIR=yes\n").unwrap();
+        let shortcut = Shortcut { env_file: Some(name.clone()), ..Default::default() };
+        assert_eq!(env_file_path(&name), dir.join(&name));
+        assert_eq!(get(&resolve_env(&shortcut).unwrap(), "FROM_CONFIG_DIR"), Some("yes"));
+
+        let missing = Shortcut { env_file: Some("missing.env".to_string()), ..Default::default() };
+        assert!(matches!(resolve_env(&missing), Err(LaunchError::NotFound(_))));
+    }
+
+    #[test]
+    fn warns_about_unset_variables_and_bad_files() {
+        let shortcut = Shortcut {
+            shortcut_type: ShortcutType::App,
+            command: "$SHORTCUT_TEST_UNSET_COMMAND/app".to_string(),
+            env: env(&[("GREETING", "hi ${SHORTCUT_TEST_UNSET_ENV}"), ("DIR", "/tmp")]),
+            working_dir: Some("${DIR}".to_string()),
+            env_file: Some("/nonexistent/shortcut-test.env".to_string()),
+            ..Default::default()
+        };
+        let warnings = warnings(&shortcut);
+        assert!(warnings.contains(&"Variable SHORTCUT_TEST_UNSET_ENV in env GREETING is not set".to_string()), "{:?}", warnings);
+        assert!(warnings.contains(&"Variable SHORTCUT_TEST_UNSET_COMMAND in command is not set".to_string()), "{:?}", warnings);
+        assert!(warnings.iter().any(|w| w.contains("shortcut-test.env")), "{:?}", warnings);
+        assert!(!warnings.iter().any(|w| w.contains("working_dir")), "{:?}", warnings);
+    }
+}
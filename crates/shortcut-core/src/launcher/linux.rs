@@ -0,0 +1,365 @@
+use super::{
+    handoff, interpreter_input, spawn, script_stdin, temp_script, with_script_path, LaunchContext, LaunchError, Launcher,
+    ScriptInput, ScriptShell, Target, KILL_GRACE,
+};
+use super::terminal::Terminal;
+use std::io;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+
+/// Launches shortcuts through xdg-open, gio, sh/bash and pkexec
+pub struct LinuxLauncher;
+
+/// Build a command for `program` in a process group of its own, so it can be stopped
+/// together with its children
+pub(crate) fn command(program: &str) -> Command {
+    let mut cmd = Command::new(program);
+    cmd.process_group(0);
+    cmd
+}
+
+/// Where the executable called `name` is on PATH
+pub(crate) fn which(name: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths).map(|dir| dir.join(name)).find(|path| path.is_file())
+}
+
+/// Whether an executable called `name` is on PATH
+fn on_path(name: &str) -> bool {
+    which(name).is_some()
+}
+
+/// Fail the way spawning `target` would if `program` does not exist. Run inside an
+/// elevation tool or a terminal, a missing program only shows up as exit code 127.
+fn check_exists(program: &str, target: &Target) -> Result<(), LaunchError> {
+    let exists = if program.contains('/') { Path::new(program).exists() } else { on_path(program) };
+    match target {
+        _ if exists => Ok(()),
+        Target::Program(_) => Err(LaunchError::NotFound(program.to_string())),
+        Target::Shell(_) => Err(LaunchError::ShellMissing(program.to_string())),
+    }
+}
+
+/// The tool used for elevation: pkexec with a polkit agent, or sudo asking for the
+/// password through SUDO_ASKPASS
+fn elevator() -> Result<&'static str, LaunchError> {
+    if on_path("pkexec") {
+        Ok("pkexec")
+    } else if std::env::var_os("SUDO_ASKPASS").is_some_and(|p| !p.is_empty()) && on_path("sudo") {
+        Ok("sudo")
+    } else {
+        Err(LaunchError::ShellMissing("pkexec".to_string()))
+    }
+}
+
+// Passed on to elevated runs when set, so a graphical program can reach the session
+const SESSION_VARS: &[&str] = &["DISPLAY", "WAYLAND_DISPLAY", "XAUTHORITY", "XDG_RUNTIME_DIR"];
+
+/// Build the command running `program` with `args` as `ctx` asks for. `target` describes
+/// the program when it runs directly; elevated, the tool doing the elevation is spawned.
+fn prepare<'a>(program: &str, args: &[String], target: Target<'a>, ctx: &LaunchContext) -> Result<(Command, Target<'a>), LaunchError> {
+    if !ctx.admin {
+        let mut cmd = command(program);
+        cmd.args(args);
+        ctx.apply(&mut cmd);
+        return Ok((cmd, target));
+    }
+
+    check_exists(program, &target)?;
+
+    // pkexec and sudo reset the environment and directory, so `env` sets them up again.
+    // It execs the program itself, which keeps it named in the password prompt.
+    let tool = elevator()?;
+    let mut cmd = command(tool);
+    if tool == "sudo" {
+        cmd.args(["-A", "--"]);
+    }
+    let dir = match &ctx.working_dir {
+        Some(dir) => dir.into(),
+        None => std::env::current_dir().unwrap_or_else(|_| "/".into()),
+    };
+    cmd.arg("env").arg("--chdir").arg(dir);
+    for name in SESSION_VARS {
+        if let Some(value) = std::env::var_os(name) {
+            let mut var = std::ffi::OsString::from(format!("{}=", name));
+            var.push(value);
+            cmd.arg(var);
+        }
+    }
+    cmd.args(ctx.env.iter().map(|(k, v)| format!("{}={}", k, v)));
+    cmd.arg(program).args(args);
+    Ok((cmd, Target::Shell(tool)))
+}
+
+/// Detach the child from our stdio so nothing is printed to the tray's terminal
+fn detach(cmd: &mut Command) {
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+}
+
+/// Hidden runs write to their log when they have one and are detached otherwise
+fn hide(cmd: &mut Command, ctx: &LaunchContext) -> Result<(), LaunchError> {
+    if ctx.hidden && !ctx.redirect_output(cmd)? {
+        detach(cmd);
+    }
+    Ok(())
+}
+
+// Run as `sh -c REMOVE_AFTER sh FILE PROGRAM ARGS...` to delete FILE once the program
+// exits. The shell in the child does it, so the file goes even when the process that
+// launched it, like `shortcut run`, has long exited; the traps cover stopped runs.
+const REMOVE_AFTER: &str = r#"file=$1; shift; trap 'rm -f -- "$file"' EXIT; trap 'exit 129' HUP; trap 'exit 130' INT; trap 'exit 143' TERM; "$@""#;
+
+// Run as `sh -c REDIRECT_STDIN sh FILE PROGRAM ARGS...`: the terminal gives the
+// program its own stdin, so the script file is redirected inside it and deleted once open
+const REDIRECT_STDIN: &str = r#"file=$1; shift; exec < "$file" || exit 127; rm -f -- "$file"; exec "$@""#;
+
+/// The terminal a visible script opens in, if one is installed
+fn terminal_for(ctx: &LaunchContext) -> Option<Terminal> {
+    if ctx.hidden {
+        None
+    } else {
+        Terminal::find(ctx.terminal.as_deref())
+    }
+}
+
+impl Launcher for LinuxLauncher {
+    fn launch_app(&self, command: &str, args: &[String], ctx: &LaunchContext) -> Result<Option<Child>, LaunchError> {
+        let (mut cmd, target) = prepare(command, args, Target::Program(command), ctx)?;
+        Ok(Some(spawn(&mut cmd, target)?))
+    }
+
+    fn open_url(&self, url: &str, ctx: &LaunchContext) -> Result<Option<Child>, LaunchError> {
+        let mut cmd = Command::new("xdg-open");
+        cmd.arg(url);
+        ctx.apply(&mut cmd);
+        detach(&mut cmd);
+        handoff(&mut cmd, Target::Shell("xdg-open"))
+    }
+
+    fn open_file(&self, path: &str, ctx: &LaunchContext) -> Result<Option<Child>, LaunchError> {
+        let (mut cmd, target) = prepare("xdg-open", &[path.to_string()], Target::Shell("xdg-open"), ctx)?;
+        detach(&mut cmd);
+        handoff(&mut cmd, target)
+    }
+
+    fn open_folder(&self, path: &str, ctx: &LaunchContext) -> Result<Option<Child>, LaunchError> {
+        // gio talks to the desktop's file manager directly; fall back to xdg-open without it
+        let mut cmd = Command::new("gio");
+        cmd.args(["open", path]);
+        ctx.apply(&mut cmd);
+        detach(&mut cmd);
+        match handoff(&mut cmd, Target::Shell("gio")) {
+            Err(LaunchError::ShellMissing(_)) => {
+                let mut cmd = Command::new("xdg-open");
+                cmd.arg(path);
+                ctx.apply(&mut cmd);
+                detach(&mut cmd);
+                handoff(&mut cmd, Target::Shell("xdg-open"))
+            }
+            result => result,
+        }
+    }
+
+    fn run_script_file(&self, path: &str, args: &[String], ctx: &LaunchContext) -> Result<Option<Child>, LaunchError> {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+
+        // Program to start, the arguments before the script's own, and how it is reported
+        let (program, mut argv, target) = match extension.as_str() {
+            "sh" | "bash" => (extension.as_str(), vec![path.to_string()], Target::Shell(extension.as_str())),
+            "ps1" => (
+                "pwsh",
+                ["-ExecutionPolicy", "Bypass", "-File", path].iter().map(|a| a.to_string()).collect(),
+                Target::Shell("pwsh"),
+            ),
+            // Unknown script type - rely on the shebang and execute bit
+            _ => (path, Vec::new(), Target::Program(path)),
+        };
+        argv.extend_from_slice(args);
+
+        let (mut cmd, target) = prepare(program, &argv, target, ctx)?;
+        hide(&mut cmd, ctx)?;
+        if let Some(terminal) = terminal_for(ctx) {
+            let (Target::Program(name) | Target::Shell(name)) = target;
+            check_exists(name, &target)?;
+            let mut cmd = terminal.wrap(&cmd, ctx.keep_open);
+            return Ok(Some(spawn(&mut cmd, Target::Shell(&terminal.program))?));
+        }
+        Ok(Some(spawn(&mut cmd, target)?))
+    }
+
+    fn run_shell_script(&self, script_content: &str, shell: &ScriptShell, ctx: &LaunchContext) -> Result<Option<Child>, LaunchError> {
+        // Interpreter, its arguments and how it gets the script. A file keeps stdin free
+        // for the script itself, e.g. for `read` prompts.
+        let (program, args, input) = match shell {
+            // cmd has no Linux equivalent, so it runs through sh
+            ScriptShell::Cmd | ScriptShell::Sh => ("sh", Vec::new(), ScriptInput::File("sh")),
+            ScriptShell::Bash => ("bash", Vec::new(), ScriptInput::File("sh")),
+            ScriptShell::Zsh => ("zsh", Vec::new(), ScriptInput::File("zsh")),
+            ScriptShell::Fish => ("fish", Vec::new(), ScriptInput::File("fish")),
+            ScriptShell::Python => ("python3", Vec::new(), ScriptInput::File("py")),
+            ScriptShell::Node => ("node", Vec::new(), ScriptInput::File("js")),
+            ScriptShell::PowerShell | ScriptShell::Pwsh => {
+                ("pwsh", vec!["-NoProfile".to_string(), "-File".to_string()], ScriptInput::File("ps1"))
+            }
+            ScriptShell::Interpreter { program, args } => (program.as_str(), args.clone(), interpreter_input(args)),
+        };
+
+        let extension = match input {
+            ScriptInput::File(extension) => extension,
+            ScriptInput::Stdin => "",
+        };
+        let script = temp_script::write(script_content.as_bytes(), extension)?;
+
+        let terminal = terminal_for(ctx);
+        let mut cmd = match (&input, &terminal) {
+            (ScriptInput::File(_), _) => {
+                let mut cmd = command("sh");
+                cmd.args(["-c", REMOVE_AFTER, "sh"]).arg(&*script).arg(program);
+                cmd.args(with_script_path(&args, &script.to_string_lossy()));
+                cmd
+            }
+            (ScriptInput::Stdin, Some(_)) => {
+                let mut cmd = command("sh");
+                cmd.args(["-c", REDIRECT_STDIN, "sh"]).arg(&*script).arg(program).args(&args);
+                cmd
+            }
+            (ScriptInput::Stdin, None) => {
+                let mut cmd = command(program);
+                cmd.args(&args);
+                cmd
+            }
+        };
+        ctx.apply(&mut cmd);
+        hide(&mut cmd, ctx)?;
+        // After hide(), which points stdin at /dev/null
+        if let (ScriptInput::Stdin, None) = (&input, &terminal) {
+            cmd.stdin(script_stdin(&script)?);
+        }
+
+        // The wrapper shell runs the program, so check for it here to report it missing.
+        // On failure `script` is dropped, and with it the file.
+        check_exists(program, &Target::Shell(program))?;
+        let child = match &terminal {
+            Some(terminal) => spawn(&mut terminal.wrap(&cmd, ctx.keep_open), Target::Shell(&terminal.program))?,
+            None => spawn(&mut cmd, Target::Shell(program))?,
+        };
+        match (input, terminal) {
+            // The child has its own handle to the file
+            (ScriptInput::Stdin, None) => drop(script),
+            // Deleted by the shell wrapping the program; if that never gets to run, the
+            // sweep picks it up
+            _ => {
+                let _ = script.keep();
+            }
+        }
+        Ok(Some(child))
+    }
+
+    fn kill_process(&self, pid: u32) -> Result<(), String> {
+        // Launched processes lead their own group; signal the group to take their children too.
+        // Fall back to the single process for anything started before groups were used.
+        let group = -(pid as libc::pid_t);
+        // SAFETY: kill has no memory-safety preconditions
+        if unsafe { libc::kill(group, libc::SIGTERM) } == 0 {
+            // Whatever traps or ignores SIGTERM is killed once the grace period is over;
+            // a group that has already gone just makes this fail with ESRCH
+            std::thread::spawn(move || {
+                std::thread::sleep(KILL_GRACE);
+                // SAFETY: as above
+                unsafe { libc::kill(group, libc::SIGKILL) };
+            });
+            return Ok(());
+        }
+        // SAFETY: as above
+        if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } == 0 {
+            Ok(())
+        } else {
+            Err(format!("Failed to kill process {}: {}", pid, io::Error::last_os_error()))
+        }
+    }
+
+    fn is_running(&self, pid: u32) -> bool {
+        // Signal 0 only checks that the process exists; EPERM means it does but isn't ours
+        // SAFETY: kill has no memory-safety preconditions
+        let alive = unsafe { libc::kill(pid as libc::pid_t, 0) } == 0;
+        alive || io::Error::last_os_error().kind() == io::ErrorKind::PermissionDenied
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run `script` hidden under `sh` reading it from stdin, and return what it wrote
+    fn run_hidden_stdin(log: Option<std::fs::File>) -> String {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out.txt");
+        let shell = ScriptShell::Interpreter { program: "sh".to_string(), args: Vec::new() };
+        let ctx = LaunchContext { hidden: true, log, ..Default::default() };
+        let script = format!("echo hello > '{}'", out.display());
+        let mut child = LinuxLauncher.run_shell_script(&script, &shell, &ctx).unwrap().unwrap();
+        assert!(child.wait().unwrap().success());
+        std::fs::read_to_string(out).unwrap_or_default()
+    }
+
+    #[test]
+    fn kill_escalates_when_term_is_ignored() {
+        let mut child = command("sh").args(["-c", "trap '' TERM; sleep 30 & wait"]).spawn().unwrap();
+        // Give the shell time to set up its trap
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        LinuxLauncher.kill_process(child.id()).unwrap();
+        let status = child.wait().unwrap();
+        assert_eq!(std::os::unix::process::ExitStatusExt::signal(&status), Some(libc::SIGKILL));
+    }
+
+    #[test]
+    fn terminal_that_returns_at_once_still_runs_script() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out.txt");
+        // Starts the program in the background and exits, like a terminal that forks
+        let ctx = LaunchContext { terminal: Some(r#"sh -c '"$@" &' sh"#.to_string()), ..Default::default() };
+        // $0 is the temp script
+        let script = format!("sleep 0.2; echo \"$0\" > '{}'", out.display());
+        let mut child = LinuxLauncher.run_shell_script(&script, &ScriptShell::Sh, &ctx).unwrap().unwrap();
+        assert!(child.wait().unwrap().success());
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        let script_path = loop {
+            match std::fs::read_to_string(&out) {
+                Ok(path) if path.ends_with('\n') => break PathBuf::from(path.trim_end()),
+                _ if std::time::Instant::now() < deadline => std::thread::sleep(std::time::Duration::from_millis(50)),
+                _ => panic!("script never ran"),
+            }
+        };
+        while script_path.exists() && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+        assert!(!script_path.exists(), "{} was left behind", script_path.display());
+    }
+
+    #[test]
+    fn script_file_is_removed_by_the_child() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out.txt");
+        let ctx = LaunchContext { hidden: true, ..Default::default() };
+        let script = format!("echo \"$0\" > '{}'", out.display());
+        let mut child = LinuxLauncher.run_shell_script(&script, &ScriptShell::Sh, &ctx).unwrap().unwrap();
+        assert!(child.wait().unwrap().success());
+        let script_path = PathBuf::from(std::fs::read_to_string(&out).unwrap().trim_end());
+        assert!(script_path.starts_with(std::env::temp_dir()));
+        assert!(!script_path.exists(), "{} was left behind", script_path.display());
+    }
+
+    #[test]
+    fn hidden_interpreter_reads_script_from_stdin() {
+        assert_eq!(run_hidden_stdin(None), "hello\n");
+        assert_eq!(run_hidden_stdin(Some(tempfile::tempfile().unwrap())), "hello\n");
+    }
+}
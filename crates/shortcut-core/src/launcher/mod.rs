@@ -0,0 +1,282 @@
+//! Platform launch backends.
+//!
+//! Every shortcut type maps onto one method of [`Launcher`]. Each supported
+//! platform provides its own implementation and [`platform`] returns the one
+//! matching the current target.
+
+#[cfg(target_os = "windows")]
+mod windows;
+#[cfg(target_os = "linux")]
+mod linux;
+mod temp_script;
+#[cfg(target_os = "linux")]
+mod terminal;
+
+#[cfg(target_os = "windows")]
+pub use windows::WindowsLauncher as PlatformLauncher;
+#[cfg(target_os = "linux")]
+pub use linux::LinuxLauncher as PlatformLauncher;
+pub use temp_script::sweep_temp_scripts;
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// How long a stopped process gets to exit by itself before it is killed outright
+pub const KILL_GRACE: Duration = Duration::from_secs(3);
+
+/// Why a shortcut could not be started
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LaunchError {
+    /// The program, script, file or folder does not exist
+    NotFound(String),
+    /// The target exists but may not be executed or opened
+    PermissionDenied(String),
+    /// The working directory is missing or not a directory
+    BadWorkingDir(String),
+    /// The shell or helper needed to run the shortcut is not installed
+    ShellMissing(String),
+    /// The shortcut is incomplete, e.g. a shell shortcut without a script
+    Invalid(String),
+    /// Any other failure to start the process
+    Failed(String),
+}
+
+impl LaunchError {
+    /// Stable identifier the frontend can match on
+    pub fn kind(&self) -> &'static str {
+        match self {
+            LaunchError::NotFound(_) => "not_found",
+            LaunchError::PermissionDenied(_) => "permission_denied",
+            LaunchError::BadWorkingDir(_) => "bad_working_dir",
+            LaunchError::ShellMissing(_) => "shell_missing",
+            LaunchError::Invalid(_) => "invalid",
+            LaunchError::Failed(_) => "failed",
+        }
+    }
+}
+
+impl fmt::Display for LaunchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LaunchError::NotFound(target) => write!(f, "Not found: {}", target),
+            LaunchError::PermissionDenied(target) => write!(f, "Permission denied: {}", target),
+            LaunchError::BadWorkingDir(dir) => write!(f, "Working directory does not exist: {}", dir),
+            LaunchError::ShellMissing(shell) => write!(f, "{} is not installed or not on PATH", shell),
+            LaunchError::Invalid(message) | LaunchError::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for LaunchError {}
+
+/// Sent to the frontend as `{ kind, message }`
+impl Serialize for LaunchError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("LaunchError", 2)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+impl From<LaunchError> for String {
+    fn from(e: LaunchError) -> Self {
+        e.to_string()
+    }
+}
+
+/// What a spawned command is, which decides how a missing executable is reported
+pub(crate) enum Target<'a> {
+    /// The shortcut's own program or script
+    Program(&'a str),
+    /// A shell or helper (sh, cmd, xdg-open, pkexec) that runs the shortcut
+    Shell(&'a str),
+}
+
+/// Start `cmd`, describing a failure in terms of `target`
+pub(crate) fn spawn(cmd: &mut Command, target: Target) -> Result<Child, LaunchError> {
+    cmd.spawn().map_err(|e| match (e.kind(), target) {
+        (io::ErrorKind::NotFound, Target::Program(name)) => LaunchError::NotFound(name.to_string()),
+        (io::ErrorKind::NotFound, Target::Shell(name)) => LaunchError::ShellMissing(name.to_string()),
+        (io::ErrorKind::PermissionDenied, Target::Program(name) | Target::Shell(name)) => {
+            LaunchError::PermissionDenied(name.to_string())
+        }
+        (_, Target::Program(name) | Target::Shell(name)) => {
+            LaunchError::Failed(format!("Failed to start {}: {}", name, e))
+        }
+    })
+}
+
+/// How the process of an app or script is started
+#[derive(Debug, Default)]
+pub struct LaunchContext {
+    pub working_dir: Option<String>,
+    /// Variables set for the process on top of the inherited environment
+    pub env: Vec<(String, String)>,
+    /// Run without a console window
+    pub hidden: bool,
+    pub admin: bool,
+    /// Terminal emulator visible scripts open in on Linux, detected when not set
+    pub terminal: Option<String>,
+    /// Keep the console or terminal window open after a visible script exits
+    pub keep_open: bool,
+    /// Receives stdout and stderr of hidden runs, see [`crate::logs`]
+    pub log: Option<File>,
+}
+
+impl LaunchContext {
+    /// Set the working directory and environment on `cmd`
+    pub(crate) fn apply(&self, cmd: &mut Command) {
+        if let Some(dir) = &self.working_dir {
+            cmd.current_dir(dir);
+        }
+        cmd.envs(self.env.iter().map(|(k, v)| (k, v)));
+    }
+
+    /// Send the output of a hidden run to the log. Returns false when there is no log.
+    pub(crate) fn redirect_output(&self, cmd: &mut Command) -> Result<bool, LaunchError> {
+        let Some(log) = &self.log else {
+            return Ok(false);
+        };
+        let clone = |file: &File| {
+            file.try_clone()
+                .map_err(|e| LaunchError::Failed(format!("Failed to open run log: {}", e)))
+        };
+        cmd.stdin(Stdio::null())
+            .stdout(clone(log)?)
+            .stderr(clone(log)?);
+        Ok(true)
+    }
+}
+
+/// Program an inline script runs under
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ScriptShell {
+    /// cmd.exe; runs through sh where there is no cmd
+    Cmd,
+    PowerShell,
+    Pwsh,
+    Sh,
+    Bash,
+    Zsh,
+    Fish,
+    Python,
+    Node,
+    /// Any other program. `{script}` in `args` is replaced with the path of the script
+    /// file; without it the script is written to the program's stdin.
+    Interpreter { program: String, args: Vec<String> },
+}
+
+impl Default for ScriptShell {
+    fn default() -> Self {
+        if cfg!(windows) {
+            ScriptShell::Cmd
+        } else {
+            ScriptShell::Sh
+        }
+    }
+}
+
+impl FromStr for ScriptShell {
+    type Err = String;
+
+    /// Parse a named shell; `interpreter` needs a program and is built directly
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "cmd" => Ok(ScriptShell::Cmd),
+            "powershell" => Ok(ScriptShell::PowerShell),
+            "pwsh" => Ok(ScriptShell::Pwsh),
+            "sh" => Ok(ScriptShell::Sh),
+            "bash" => Ok(ScriptShell::Bash),
+            "zsh" => Ok(ScriptShell::Zsh),
+            "fish" => Ok(ScriptShell::Fish),
+            "python" => Ok(ScriptShell::Python),
+            "node" => Ok(ScriptShell::Node),
+            other => Err(format!("Unknown shell: {}", other)),
+        }
+    }
+}
+
+/// How the script body reaches the interpreter
+pub(crate) enum ScriptInput {
+    /// A temp file with this extension, passed as the last argument or in place of `{script}`
+    File(&'static str),
+    /// Read from stdin. The child gets the open temp file, which is deleted right away.
+    Stdin,
+}
+
+/// The input for a generic interpreter, see [`ScriptShell::Interpreter`]
+pub(crate) fn interpreter_input(args: &[String]) -> ScriptInput {
+    if args.iter().any(|a| a.contains(SCRIPT_PLACEHOLDER)) {
+        ScriptInput::File("")
+    } else {
+        ScriptInput::Stdin
+    }
+}
+
+const SCRIPT_PLACEHOLDER: &str = "{script}";
+
+/// `args` with the script file put in place of `{script}`, or appended when there is none
+pub(crate) fn with_script_path(args: &[String], path: &str) -> Vec<String> {
+    if args.iter().any(|a| a.contains(SCRIPT_PLACEHOLDER)) {
+        args.iter().map(|a| a.replace(SCRIPT_PLACEHOLDER, path)).collect()
+    } else {
+        args.iter().cloned().chain(std::iter::once(path.to_string())).collect()
+    }
+}
+
+/// `script` opened again for the child's stdin
+pub(crate) fn script_stdin(script: &Path) -> Result<File, LaunchError> {
+    File::open(script).map_err(|e| LaunchError::Failed(format!("Failed to open temp script: {}", e)))
+}
+
+/// A backend able to start every kind of shortcut on one platform.
+///
+/// Launch methods return once the process has been started; they do not wait for it.
+/// They hand back the process when it is the shortcut itself, and `None` when a helper
+/// (xdg-open, `cmd /C start`, an elevation prompt) passes the work on to another program.
+pub trait Launcher {
+    /// Launch an application
+    fn launch_app(&self, command: &str, args: &[String], ctx: &LaunchContext) -> Result<Option<Child>, LaunchError>;
+
+    /// Open URL in default browser
+    fn open_url(&self, url: &str, ctx: &LaunchContext) -> Result<Option<Child>, LaunchError>;
+
+    /// Open file with default application
+    fn open_file(&self, path: &str, ctx: &LaunchContext) -> Result<Option<Child>, LaunchError>;
+
+    /// Open folder in the file manager
+    fn open_folder(&self, path: &str, ctx: &LaunchContext) -> Result<Option<Child>, LaunchError>;
+
+    /// Run a script file (.bat, .ps1, .sh, etc.)
+    fn run_script_file(&self, path: &str, args: &[String], ctx: &LaunchContext) -> Result<Option<Child>, LaunchError>;
+
+    /// Run inline script content under `shell`; `ctx.admin` is not supported here
+    fn run_shell_script(&self, script_content: &str, shell: &ScriptShell, ctx: &LaunchContext) -> Result<Option<Child>, LaunchError>;
+
+    /// Stop a process started by one of the methods above, along with every process it
+    /// started. Returns once asked to stop; anything still running after [`KILL_GRACE`]
+    /// is killed from a thread of this process.
+    fn kill_process(&self, pid: u32) -> Result<(), String>;
+
+    /// Whether a process with this PID still exists
+    fn is_running(&self, pid: u32) -> bool;
+}
+
+/// Start a helper that hands off to another program and reap it in the background
+pub(crate) fn handoff(cmd: &mut Command, target: Target) -> Result<Option<Child>, LaunchError> {
+    let mut child = spawn(cmd, target)?;
+    std::thread::spawn(move || child.wait());
+    Ok(None)
+}
+
+/// Launcher for the platform this binary was built for
+pub fn platform() -> PlatformLauncher {
+    PlatformLauncher
+}
@@ -0,0 +1,139 @@
+//! Temp files for inline scripts that a shell can only run from a file.
+//!
+//! Each run gets its own file in a directory only the current user can access:
+//! `$XDG_RUNTIME_DIR/shortcut-rs`, else `shortcut-rs-<uid>` under the system temp
+//! directory on Unix, and `shortcut-rs` under the per-user temp directory on Windows.
+//! The launcher deletes it once the script's process exits; files left behind because
+//! the app quit first are removed by [`sweep_temp_scripts`].
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tempfile::TempPath;
+
+use super::LaunchError;
+
+const PREFIX: &str = "shortcut_script_";
+
+/// Scripts older than this are assumed to be finished
+const STALE_AFTER: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Directory holding the script files
+#[cfg(unix)]
+fn script_dir() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir).join("shortcut-rs"),
+        // SAFETY: getuid has no preconditions and cannot fail
+        None => std::env::temp_dir().join(format!("shortcut-rs-{}", unsafe { libc::getuid() })),
+    }
+}
+
+/// Directory holding the script files
+#[cfg(windows)]
+fn script_dir() -> PathBuf {
+    std::env::temp_dir().join("shortcut-rs")
+}
+
+/// Create `dir` if needed and make sure no other user can get at what is put in it. On
+/// Unix an existing directory must be owned by the current user and have mode 0700;
+/// anyone else able to write there could swap a script between writing and running it.
+fn private_dir(dir: &Path) -> io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(dir)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        // Not following links, which could point anywhere
+        let meta = fs::symlink_metadata(dir)?;
+        // SAFETY: getuid has no preconditions and cannot fail
+        let uid = unsafe { libc::getuid() };
+        if !meta.is_dir() || meta.uid() != uid || meta.mode() & 0o777 != 0o700 {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{} must be a directory owned by you with mode 0700", dir.display()),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Write `content` to a new, uniquely named script file ending in `.{extension}`, if given
+pub(crate) fn write(content: &[u8], extension: &str) -> Result<TempPath, LaunchError> {
+    let failed = |e: io::Error| LaunchError::Failed(format!("Failed to write temp script: {}", e));
+    let dir = script_dir();
+    private_dir(&dir).map_err(failed)?;
+
+    // Created with mode 0600 on Unix; on Windows the per-user temp directory's ACL applies
+    let mut file = tempfile::Builder::new()
+        .prefix(PREFIX)
+        .suffix(&if extension.is_empty() { String::new() } else { format!(".{}", extension) })
+        .tempfile_in(&dir)
+        .map_err(failed)?;
+    file.write_all(content).map_err(failed)?;
+    // Closed here so the shell can open it
+    Ok(file.into_temp_path())
+}
+
+/// Delete script files left by earlier runs of the app. Call once on startup.
+pub fn sweep_temp_scripts() {
+    // Older releases wrote `shortcut_script_<pid>.*` straight into the temp directory,
+    // and later ones into a `shortcut-rs` directory there shared by all users
+    for dir in [script_dir(), std::env::temp_dir().join("shortcut-rs"), std::env::temp_dir()] {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let is_script = entry.file_name().to_string_lossy().starts_with(PREFIX);
+            let stale = entry
+                .metadata()
+                .and_then(|meta| meta.modified())
+                .ok()
+                .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                .is_some_and(|age| age > STALE_AFTER);
+            if is_script && stale {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::{symlink, PermissionsExt};
+
+    #[test]
+    fn creates_private_dir() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("scripts");
+        private_dir(&dir).unwrap();
+        assert_eq!(fs::metadata(&dir).unwrap().permissions().mode() & 0o777, 0o700);
+        // Existing and still private
+        private_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_dir_others_can_use() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("scripts");
+        fs::create_dir(&dir).unwrap();
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o777)).unwrap();
+        assert_eq!(private_dir(&dir).unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+    }
+
+    #[test]
+    fn rejects_symlink() {
+        let root = tempfile::tempdir().unwrap();
+        let target = root.path().join("elsewhere");
+        private_dir(&target).unwrap();
+        let link = root.path().join("scripts");
+        symlink(&target, &link).unwrap();
+        assert!(private_dir(&link).is_err());
+    }
+}
@@ -0,0 +1,118 @@
+//! Running scripts in a terminal emulator on Linux.
+//!
+//! The Linux counterpart of `CREATE_NEW_CONSOLE`: visible scripts open in a terminal
+//! window so their output can be read. The terminal is the one set in the config,
+//! `$TERMINAL`, or the first of [`KNOWN`] found on PATH.
+
+use std::ffi::OsStr;
+use std::path::Path;
+use std::process::Command;
+
+use super::linux::{command, which};
+
+/// Terminals looked for when none is configured, in order of preference
+const KNOWN: &[&str] = &[
+    "x-terminal-emulator",
+    "gnome-terminal",
+    "konsole",
+    "alacritty",
+    "kitty",
+    "wezterm",
+    "xfce4-terminal",
+    "xterm",
+];
+
+// Run as `sh -c KEEP_OPEN sh PROGRAM ARGS...` to wait for Enter once the program exits
+const KEEP_OPEN: &str =
+    r#""$@"; status=$?; printf '\n[Exited with code %s. Press Enter to close]' "$status"; read -r _; exit "$status""#;
+
+/// A terminal emulator and the arguments after which it takes the program to run
+pub(crate) struct Terminal {
+    pub program: String,
+    args: Vec<String>,
+}
+
+impl Terminal {
+    /// The terminal to use: `configured`, `$TERMINAL` or the first known one installed.
+    ///
+    /// A single name gets the arguments that terminal needs; a longer command line is
+    /// used as-is, with the program to run appended.
+    pub fn find(configured: Option<&str>) -> Option<Terminal> {
+        let configured = configured
+            .map(str::to_string)
+            .or_else(|| std::env::var("TERMINAL").ok())
+            .filter(|t| !t.trim().is_empty());
+        let mut words = match configured {
+            Some(terminal) => shell_words::split(&terminal).ok()?,
+            None => vec![KNOWN.iter().find(|name| which(name).is_some())?.to_string()],
+        };
+        let program = words.remove(0);
+        if !words.is_empty() {
+            return Some(Terminal { program, args: words });
+        }
+        let program = resolve_alternative(program);
+        let name = Path::new(&program).file_name().and_then(|n| n.to_str()).unwrap_or(&program);
+        let args = exec_args(name).iter().map(|a| a.to_string()).collect();
+        Some(Terminal { program, args })
+    }
+
+    /// A command running `inner` in this terminal, with the same environment and
+    /// directory. With `keep_open` the window waits for Enter once `inner` exits.
+    pub fn wrap(&self, inner: &Command, keep_open: bool) -> Command {
+        let mut argv: Vec<&OsStr> = Vec::new();
+        if keep_open {
+            argv.extend(["sh", "-c", KEEP_OPEN, "sh"].map(OsStr::new));
+        }
+        argv.push(inner.get_program());
+        argv.extend(inner.get_args());
+
+        let mut cmd = command(&self.program);
+        cmd.args(&self.args).args(argv);
+        if let Some(dir) = inner.get_current_dir() {
+            cmd.current_dir(dir);
+        }
+        for (key, value) in inner.get_envs() {
+            match value {
+                Some(value) => cmd.env(key, value),
+                None => cmd.env_remove(key),
+            };
+        }
+        cmd
+    }
+}
+
+/// Arguments that make `terminal` run the program given after them and stay attached
+/// until it exits, so the run can be tracked. A terminal given as a full command line
+/// has to do the same, or its runs are shown as finished right away.
+fn exec_args(terminal: &str) -> &'static [&'static str] {
+    match terminal {
+        "gnome-terminal" => &["--wait", "--"],
+        "konsole" => &["--nofork", "-e"],
+        "wezterm" => &["start", "--always-new-process", "--"],
+        "xfce4-terminal" => &["--disable-server", "-x"],
+        "kitty" => &[],
+        // x-terminal-emulator, alacritty, xterm and most others take xterm's -e
+        _ => &["-e"],
+    }
+}
+
+/// The terminal Debian's x-terminal-emulator alternative points to, when it is one we
+/// know the arguments of; its wrappers do not pass on options like gnome-terminal's --wait
+fn resolve_alternative(program: String) -> String {
+    if program != "x-terminal-emulator" {
+        return program;
+    }
+    let target = which(&program)
+        .and_then(|path| path.canonicalize().ok())
+        .and_then(|path| path.file_name().map(|n| n.to_string_lossy().into_owned()));
+    match target {
+        Some(target) => {
+            let name = target.trim_end_matches(".wrapper");
+            match KNOWN.iter().find(|known| **known == name) {
+                Some(known) if which(known).is_some() => known.to_string(),
+                _ => program,
+            }
+        }
+        None => program,
+    }
+}
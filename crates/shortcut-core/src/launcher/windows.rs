@@ -0,0 +1,301 @@
+use super::{
+    handoff, interpreter_input, spawn, script_stdin, temp_script, with_script_path, LaunchContext, LaunchError, Launcher,
+    ScriptInput, ScriptShell, Target,
+};
+use crate::args::{join_cmd, join_windows};
+use std::io;
+use std::os::windows::process::CommandExt;
+use std::process::{Child, Command, Stdio};
+use tempfile::TempPath;
+use windows_sys::Win32::Foundation::{ERROR_CANCELLED, ERROR_FILE_NOT_FOUND, ERROR_PATH_NOT_FOUND};
+use windows_sys::Win32::UI::Shell::{ShellExecuteExW, SEE_MASK_FLAG_NO_UI, SEE_MASK_NOASYNC, SHELLEXECUTEINFOW};
+use windows_sys::Win32::UI::WindowsAndMessaging::{SW_HIDE, SW_SHOWNORMAL};
+
+// Windows constants for CreateProcess
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+const CREATE_NEW_CONSOLE: u32 = 0x00000010;
+
+/// Launches shortcuts through cmd, explorer and PowerShell
+pub struct WindowsLauncher;
+
+/// Whether the console of a visible run should stay open once the script ends
+fn keep_open(ctx: &LaunchContext) -> bool {
+    ctx.keep_open && !ctx.hidden
+}
+
+/// cmd's switch for running a command; /K leaves the window open afterwards
+fn cmd_switch(ctx: &LaunchContext) -> &'static str {
+    if keep_open(ctx) {
+        "/K"
+    } else {
+        "/C"
+    }
+}
+
+/// Arguments for cmd that run `line` (a program or builtin and its arguments) with
+/// `switch`. With /S cmd strips just the outer quotes; [`join_cmd`] escapes the rest so
+/// that `&`, `|` and the like in a path or argument never start another command.
+fn cmd_args(switch: &str, line: &[&str]) -> String {
+    let line: Vec<String> = line.iter().map(|a| a.to_string()).collect();
+    format!("/S {} \"{}\"", switch, join_cmd(&line))
+}
+
+/// PowerShell arguments that run the script file given after them
+fn powershell_args(ctx: &LaunchContext) -> Vec<String> {
+    let no_exit = keep_open(ctx).then_some("-NoExit");
+    no_exit
+        .into_iter()
+        .chain(["-ExecutionPolicy", "Bypass", "-File"])
+        .map(str::to_string)
+        .collect()
+}
+
+/// Start `file` with `args` through the UAC prompt (ShellExecuteEx with the runas verb).
+///
+/// The elevated process gets a fresh environment, so `ctx.env` does not reach it, and
+/// its output cannot be captured. It is not our child, so there is nothing to track.
+fn run_elevated(file: &str, args: &[String], ctx: &LaunchContext) -> Result<Option<Child>, LaunchError> {
+    run_elevated_raw(file, &join_windows(args), ctx)
+}
+
+/// Like [`run_elevated`], with `parameters` already quoted into a command line
+fn run_elevated_raw(file: &str, parameters: &str, ctx: &LaunchContext) -> Result<Option<Child>, LaunchError> {
+    let verb = wide("runas");
+    let file_name = wide(file);
+    let parameters = wide(parameters);
+    let directory = ctx.working_dir.as_deref().map(wide);
+    let mut info = SHELLEXECUTEINFOW {
+        cbSize: std::mem::size_of::<SHELLEXECUTEINFOW>() as u32,
+        // Report failures to us instead of in a message box
+        fMask: SEE_MASK_NOASYNC | SEE_MASK_FLAG_NO_UI,
+        lpVerb: verb.as_ptr(),
+        lpFile: file_name.as_ptr(),
+        lpParameters: parameters.as_ptr(),
+        lpDirectory: directory.as_ref().map_or(std::ptr::null(), |dir| dir.as_ptr()),
+        nShow: if ctx.hidden { SW_HIDE } else { SW_SHOWNORMAL },
+        ..Default::default()
+    };
+    // SAFETY: every pointer in `info` is a NUL-terminated buffer that outlives the call
+    if unsafe { ShellExecuteExW(&mut info) } != 0 {
+        return Ok(None);
+    }
+
+    let error = io::Error::last_os_error();
+    Err(match error.raw_os_error().map(|code| code as u32) {
+        Some(ERROR_CANCELLED) => LaunchError::PermissionDenied(format!("{} (the elevation prompt was declined)", file)),
+        Some(ERROR_FILE_NOT_FOUND | ERROR_PATH_NOT_FOUND) => LaunchError::NotFound(file.to_string()),
+        _ => LaunchError::Failed(format!("Failed to start {} as administrator: {}", file, error)),
+    })
+}
+
+/// `s` as a NUL-terminated UTF-16 string
+fn wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// Delete `script` once `child` has exited. A hidden PowerShell of its own waits for it,
+/// so the file goes even when the process that launched it, like `shortcut run`, has long
+/// exited. If that cannot start, the file is left for [`temp_script::sweep_temp_scripts`].
+fn remove_on_exit(script: TempPath, child: &Child) {
+    // Single quotes take everything literally but themselves, which are doubled
+    let path = script.to_string_lossy().replace('\'', "''");
+    let command = format!(
+        "Wait-Process -Id {} -ErrorAction SilentlyContinue; Remove-Item -LiteralPath '{}' -Force -ErrorAction SilentlyContinue",
+        child.id(),
+        path
+    );
+    // Not waited on; it ends by itself once the file is gone
+    let _ = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &command])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .creation_flags(CREATE_NO_WINDOW)
+        .spawn();
+    let _ = script.keep();
+}
+
+/// Hidden runs get no console window and write to their log when they have one
+fn hide(cmd: &mut Command, ctx: &LaunchContext) -> Result<(), LaunchError> {
+    cmd.creation_flags(CREATE_NO_WINDOW);
+    ctx.redirect_output(cmd)?;
+    Ok(())
+}
+
+impl Launcher for WindowsLauncher {
+    fn launch_app(&self, command: &str, args: &[String], ctx: &LaunchContext) -> Result<Option<Child>, LaunchError> {
+        if ctx.admin {
+            run_elevated(command, args, ctx)
+        } else {
+            let mut cmd = Command::new(command);
+            cmd.args(args);
+            ctx.apply(&mut cmd);
+            Ok(Some(spawn(&mut cmd, Target::Program(command))?))
+        }
+    }
+
+    fn open_url(&self, url: &str, ctx: &LaunchContext) -> Result<Option<Child>, LaunchError> {
+        let mut cmd = Command::new("cmd");
+        cmd.raw_arg(cmd_args("/C", &["start", "", url]))
+            .creation_flags(CREATE_NO_WINDOW);
+        ctx.apply(&mut cmd);
+        handoff(&mut cmd, Target::Shell("cmd"))
+    }
+
+    fn open_file(&self, path: &str, ctx: &LaunchContext) -> Result<Option<Child>, LaunchError> {
+        if ctx.admin {
+            run_elevated(path, &[], ctx)
+        } else {
+            let mut cmd = Command::new("cmd");
+            cmd.raw_arg(cmd_args("/C", &["start", "", path]))
+                .creation_flags(CREATE_NO_WINDOW);
+            ctx.apply(&mut cmd);
+            handoff(&mut cmd, Target::Shell("cmd"))
+        }
+    }
+
+    fn open_folder(&self, path: &str, ctx: &LaunchContext) -> Result<Option<Child>, LaunchError> {
+        let mut cmd = Command::new("explorer");
+        cmd.arg(path);
+        ctx.apply(&mut cmd);
+        handoff(&mut cmd, Target::Shell("explorer"))
+    }
+
+    fn run_script_file(&self, path: &str, args: &[String], ctx: &LaunchContext) -> Result<Option<Child>, LaunchError> {
+        let extension = std::path::Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+
+        match extension.as_str() {
+            "ps1" => {
+                // PowerShell script
+                let mut argv = powershell_args(ctx);
+                argv.push(path.to_string());
+                argv.extend_from_slice(args);
+                if ctx.admin {
+                    return run_elevated("powershell", &argv, ctx);
+                }
+                let mut cmd = Command::new("powershell");
+                cmd.args(&argv);
+                ctx.apply(&mut cmd);
+                if ctx.hidden {
+                    hide(&mut cmd, ctx)?;
+                }
+                Ok(Some(spawn(&mut cmd, Target::Shell("powershell"))?))
+            }
+            "bat" | "cmd" => {
+                // Batch script
+                let mut script = vec![path];
+                script.extend(args.iter().map(String::as_str));
+                let cmd_line = cmd_args(cmd_switch(ctx), &script);
+                if ctx.admin {
+                    return run_elevated_raw("cmd", &cmd_line, ctx);
+                }
+                let mut cmd = Command::new("cmd");
+                cmd.raw_arg(cmd_line);
+                ctx.apply(&mut cmd);
+                if ctx.hidden {
+                    hide(&mut cmd, ctx)?;
+                } else {
+                    cmd.creation_flags(CREATE_NEW_CONSOLE);
+                }
+                Ok(Some(spawn(&mut cmd, Target::Shell("cmd"))?))
+            }
+            _ => {
+                // Unknown script type - try to run directly
+                if ctx.admin {
+                    return run_elevated(path, args, ctx);
+                }
+                let mut cmd = Command::new(path);
+                cmd.args(args);
+                ctx.apply(&mut cmd);
+                if ctx.hidden {
+                    hide(&mut cmd, ctx)?;
+                }
+                Ok(Some(spawn(&mut cmd, Target::Program(path))?))
+            }
+        }
+    }
+
+    fn run_shell_script(&self, script_content: &str, shell: &ScriptShell, ctx: &LaunchContext) -> Result<Option<Child>, LaunchError> {
+        let script_args = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        // Interpreter, its arguments and how it gets the script. POSIX shells read it from
+        // stdin: under WSL they could not open a Windows path.
+        let (program, args, input) = match shell {
+            ScriptShell::Cmd => ("cmd", script_args(&[cmd_switch(ctx)]), ScriptInput::File("bat")),
+            ScriptShell::PowerShell => ("powershell", powershell_args(ctx), ScriptInput::File("ps1")),
+            ScriptShell::Pwsh => ("pwsh", powershell_args(ctx), ScriptInput::File("ps1")),
+            ScriptShell::Sh => ("sh", Vec::new(), ScriptInput::Stdin),
+            ScriptShell::Bash => ("bash", Vec::new(), ScriptInput::Stdin),
+            ScriptShell::Zsh => ("zsh", Vec::new(), ScriptInput::Stdin),
+            ScriptShell::Fish => ("fish", Vec::new(), ScriptInput::Stdin),
+            ScriptShell::Python => ("python", Vec::new(), ScriptInput::File("py")),
+            ScriptShell::Node => ("node", Vec::new(), ScriptInput::File("js")),
+            ScriptShell::Interpreter { program, args } => (program.as_str(), args.clone(), interpreter_input(args)),
+        };
+
+        let extension = match input {
+            ScriptInput::File(extension) => extension,
+            ScriptInput::Stdin => "",
+        };
+        let content = if *shell == ScriptShell::Cmd {
+            format!("@echo off\r\n{}\r\n", script_content.replace('\n', "\r\n"))
+        } else {
+            script_content.to_string()
+        };
+        let script = temp_script::write(content.as_bytes(), extension)?;
+
+        let mut cmd = Command::new(program);
+        match input {
+            ScriptInput::File(_) => cmd.args(with_script_path(&args, &script.to_string_lossy())),
+            ScriptInput::Stdin => cmd.args(&args),
+        };
+        ctx.apply(&mut cmd);
+        if ctx.hidden {
+            hide(&mut cmd, ctx)?;
+        } else if *shell == ScriptShell::Cmd {
+            cmd.creation_flags(CREATE_NEW_CONSOLE);
+        }
+        if let ScriptInput::Stdin = input {
+            cmd.stdin(script_stdin(&script)?);
+        }
+        // On failure `script` is dropped, and with it the file
+        let child = spawn(&mut cmd, Target::Shell(program))?;
+        match input {
+            ScriptInput::File(_) => remove_on_exit(script, &child),
+            // The child has its own handle to the file, which stays readable until closed
+            ScriptInput::Stdin => drop(script),
+        }
+        Ok(Some(child))
+    }
+
+    fn kill_process(&self, pid: u32) -> Result<(), String> {
+        let output = Command::new("taskkill")
+            // /T takes the whole process tree, e.g. everything a .bat started
+            .args(["/PID", &pid.to_string(), "/T", "/F"])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .map_err(|e| format!("Failed to run taskkill: {}", e))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Failed to kill process {}: {}",
+                pid,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    }
+
+    fn is_running(&self, pid: u32) -> bool {
+        // tasklist prints an INFO line instead of a row when nothing matches
+        Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/NH", "/FO", "CSV"])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .map(|o| String::from_utf8_lossy(&o.stdout).contains(&format!("\"{}\"", pid)))
+            .unwrap_or(false)
+    }
+}
@@ -0,0 +1,155 @@
+//! Config and launch core shared by the shortcut-rs tray app and other front-ends.
+//!
+//! Nothing in this crate depends on a GUI, so it can be used and tested headless.
+
+pub mod args;
+pub mod backup;
+pub mod config;
+pub mod env;
+pub mod launcher;
+pub mod logs;
+pub mod migrate;
+pub mod process;
+pub mod sequence;
+pub mod store;
+pub mod template;
+
+pub use args::{join_args, split_args};
+pub use backup::{list_backups, BackupInfo};
+pub use config::{
+    expand_env_vars, expand_env_vars_checked, expand_env_vars_with, get_config_path, load_config, new_shortcut_id, parse_config, save_config, set_config_path,
+    AppConfig, CommandArgs, ConfigError, SequenceStep, Shortcut, ShortcutType,
+};
+pub use launcher::{sweep_temp_scripts, LaunchContext, LaunchError, Launcher, ScriptShell};
+pub use logs::{list_logs, read_log, LogInfo};
+pub use migrate::CONFIG_VERSION;
+pub use process::{read_processes, ProcessInfo, ProcessRegistry, ProcessStatus};
+pub use store::ConfigStore;
+
+use std::fs::File;
+use std::path::Path;
+use std::process::Child;
+
+/// Launch a shortcut with the backend for the current platform, returning the started
+/// process when there is one to track (see [`Launcher`]). Sequences need the config to
+/// look up their steps and are run through [`sequence`] instead.
+///
+/// Paths that must exist are checked up front: helpers like xdg-open or `cmd /C start`
+/// start fine and only fail later, where the error can no longer be reported.
+pub fn launch(shortcut: &Shortcut) -> Result<Option<Child>, LaunchError> {
+    launch_with(shortcut, LaunchOptions::default())
+}
+
+/// How a launch goes beyond what the shortcut itself says
+#[derive(Default)]
+pub struct LaunchOptions {
+    // Receives the output of a hidden run
+    pub log: Option<File>,
+    // Terminal visible scripts open in on Linux, see `AppConfig::terminal`
+    pub terminal: Option<String>,
+}
+
+/// Like [`launch`], with the extra settings in `options`
+pub fn launch_with(shortcut: &Shortcut, options: LaunchOptions) -> Result<Option<Child>, LaunchError> {
+    let vars = env::resolve_env(shortcut)?;
+    let expanded_command = env::expand_with(&shortcut.command, &vars);
+    let ctx = LaunchContext {
+        working_dir: shortcut.working_dir.as_ref().map(|d| env::expand_with(d, &vars)),
+        env: vars,
+        hidden: shortcut.hidden,
+        admin: shortcut.admin,
+        terminal: options.terminal,
+        keep_open: shortcut.keep_open,
+        log: options.log,
+    };
+    let args = match &shortcut.args {
+        Some(args) => args.to_argv().map_err(LaunchError::Invalid)?,
+        None => Vec::new(),
+    };
+    let launcher = launcher::platform();
+
+    if let Some(dir) = &ctx.working_dir {
+        if !Path::new(dir).is_dir() {
+            return Err(LaunchError::BadWorkingDir(dir.clone()));
+        }
+    }
+    let must_exist = matches!(
+        shortcut.shortcut_type,
+        ShortcutType::File | ShortcutType::Folder | ShortcutType::Script
+    );
+    // A relative path is looked up in the directory the launch happens in
+    let resolved = match &ctx.working_dir {
+        Some(dir) => Path::new(dir).join(&expanded_command),
+        None => Path::new(&expanded_command).to_path_buf(),
+    };
+    if must_exist && !resolved.exists() {
+        return Err(LaunchError::NotFound(expanded_command));
+    }
+
+    match shortcut.shortcut_type {
+        ShortcutType::App => {
+            launcher.launch_app(&expanded_command, &args, &ctx)
+        }
+        ShortcutType::Url => launcher.open_url(&expanded_command, &ctx),
+        ShortcutType::File => launcher.open_file(&expanded_command, &ctx),
+        ShortcutType::Folder => launcher.open_folder(&expanded_command, &ctx),
+        ShortcutType::Script => {
+            launcher.run_script_file(&expanded_command, &args, &ctx)
+        }
+        ShortcutType::Shell => {
+            let script_content = shortcut
+                .script
+                .as_deref()
+                .ok_or_else(|| LaunchError::Invalid("Shell shortcut has no script".to_string()))?;
+            let shell = script_shell(shortcut, &ctx.env)?;
+            launcher.run_shell_script(script_content, &shell, &ctx)
+        }
+        ShortcutType::Sequence => Err(LaunchError::Invalid(format!(
+            "{} is a sequence and has to be run with its config",
+            shortcut.name
+        ))),
+    }
+}
+
+/// The shell a `Shell` shortcut runs under
+fn script_shell(shortcut: &Shortcut, vars: &[(String, String)]) -> Result<ScriptShell, LaunchError> {
+    match shortcut.shell.as_deref().map(str::trim) {
+        None | Some("") => Ok(ScriptShell::default()),
+        Some(name) if name.eq_ignore_ascii_case("interpreter") => {
+            let program = shortcut
+                .interpreter
+                .as_deref()
+                .filter(|p| !p.trim().is_empty())
+                .ok_or_else(|| LaunchError::Invalid("Interpreter shortcut has no interpreter set".to_string()))?;
+            let args = match &shortcut.interpreter_args {
+                Some(args) => args.to_argv().map_err(LaunchError::Invalid)?,
+                None => Vec::new(),
+            };
+            Ok(ScriptShell::Interpreter { program: env::expand_with(program, vars), args })
+        }
+        Some(name) => name.parse().map_err(LaunchError::Invalid),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_script_is_found_in_the_working_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("build.sh"), "exit 0\n").unwrap();
+        let shortcut = Shortcut {
+            shortcut_type: ShortcutType::Script,
+            command: "./build.sh".to_string(),
+            working_dir: Some(dir.path().to_string_lossy().into_owned()),
+            hidden: true,
+            ..Default::default()
+        };
+        let mut child = launch(&shortcut).unwrap().unwrap();
+        assert!(child.wait().unwrap().success());
+
+        let missing = Shortcut { command: "./missing.sh".to_string(), ..shortcut };
+        assert!(matches!(launch(&missing), Err(LaunchError::NotFound(path)) if path == "./missing.sh"));
+    }
+}
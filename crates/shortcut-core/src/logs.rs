@@ -0,0 +1,138 @@
+//! Output of hidden script runs.
+//!
+//! A hidden `Script` or `Shell` shortcut has no console to write to, so its stdout
+//! and stderr go to `logs/<run id>.log` next to the config. Only the newest
+//! [`MAX_LOGS`] files are kept.
+
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::config::{get_config_path, Shortcut, ShortcutType};
+
+/// How many run logs are kept
+pub const MAX_LOGS: usize = 50;
+
+/// Logs longer than this are returned from their end only
+pub const MAX_LOG_READ: u64 = 256 * 1024;
+
+const LOG_SUFFIX: &str = ".log";
+
+/// A log written by an earlier run
+#[derive(Serialize, Clone, Debug)]
+pub struct LogInfo {
+    pub run_id: String,
+    // Local time of the last write, e.g. "2024-05-01 14:03:22"
+    pub modified: String,
+    pub size: u64,
+}
+
+/// Directory holding the run logs
+pub fn log_dir() -> PathBuf {
+    let mut dir = get_config_path();
+    dir.pop();
+    dir.push("logs");
+    dir
+}
+
+/// Whether runs of `shortcut` have their output captured
+pub fn captures_output(shortcut: &Shortcut) -> bool {
+    shortcut.hidden && matches!(shortcut.shortcut_type, ShortcutType::Script | ShortcutType::Shell)
+}
+
+/// Path of the log for `run_id`; rejects anything that is not a plain run ID
+pub fn log_path(run_id: &str) -> Result<PathBuf, String> {
+    if run_id.is_empty() || !run_id.chars().all(|c| c.is_ascii_hexdigit() || c == '-') {
+        return Err(format!("Invalid run ID: {}", run_id));
+    }
+    Ok(log_dir().join(format!("{}{}", run_id, LOG_SUFFIX)))
+}
+
+/// Create the log for a new run and drop the oldest logs beyond MAX_LOGS
+pub fn create_log(run_id: &str) -> Result<File, String> {
+    let dir = log_dir();
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create log directory: {}", e))?;
+    let file = File::create(log_path(run_id)?)
+        .map_err(|e| format!("Failed to create run log: {}", e))?;
+    prune(&dir);
+    Ok(file)
+}
+
+/// Delete the log for `run_id`, if there is one
+pub fn remove_log(run_id: &str) {
+    if let Ok(path) = log_path(run_id) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Log files with their modification time, newest first
+fn log_files(dir: &Path) -> Vec<(String, SystemTime, u64)> {
+    let mut files: Vec<(String, SystemTime, u64)> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter_map(|e| {
+                    let name = e.file_name().into_string().ok()?;
+                    let run_id = name.strip_suffix(LOG_SUFFIX)?.to_string();
+                    let meta = e.metadata().ok()?;
+                    Some((run_id, meta.modified().ok()?, meta.len()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort_unstable_by_key(|f| std::cmp::Reverse(f.1));
+    files
+}
+
+fn prune(dir: &Path) {
+    for (run_id, _, _) in log_files(dir).into_iter().skip(MAX_LOGS) {
+        let _ = fs::remove_file(dir.join(format!("{}{}", run_id, LOG_SUFFIX)));
+    }
+}
+
+/// List run logs, newest first
+pub fn list_logs() -> Vec<LogInfo> {
+    log_files(&log_dir())
+        .into_iter()
+        .map(|(run_id, modified, size)| LogInfo {
+            run_id,
+            modified: chrono::DateTime::<chrono::Local>::from(modified)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+            size,
+        })
+        .collect()
+}
+
+/// Output of the run `run_id`, at most the last MAX_LOG_READ bytes
+pub fn read_log(run_id: &str) -> Result<String, String> {
+    let path = log_path(run_id)?;
+    let mut file = File::open(&path)
+        .map_err(|e| format!("Failed to open log for run {}: {}", run_id, e))?;
+    let len = file
+        .metadata()
+        .map_err(|e| format!("Failed to read log for run {}: {}", run_id, e))?
+        .len();
+    if len > MAX_LOG_READ {
+        file.seek(SeekFrom::Start(len - MAX_LOG_READ))
+            .map_err(|e| format!("Failed to read log for run {}: {}", run_id, e))?;
+    }
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read log for run {}: {}", run_id, e))?;
+    // Scripts may print in the console's code page rather than UTF-8
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// The last `lines` lines the run `run_id` printed, if it printed anything
+pub fn log_tail(run_id: &str, lines: usize) -> Option<String> {
+    let output = read_log(run_id).ok()?;
+    let tail: Vec<&str> = output.lines().rev().filter(|l| !l.trim().is_empty()).take(lines).collect();
+    if tail.is_empty() {
+        return None;
+    }
+    Some(tail.into_iter().rev().collect::<Vec<_>>().join("\n"))
+}
@@ -0,0 +1,167 @@
+//! Upgrades config.json files written by older releases.
+//!
+//! Each entry in [`MIGRATIONS`] lifts a raw JSON document from version `n`
+//! to `n + 1`, so a file of any age is brought up to [`CONFIG_VERSION`] one
+//! step at a time before it is deserialized into [`AppConfig`](crate::AppConfig).
+
+use serde_json::{Map, Value};
+
+/// Schema version written by this release
+pub const CONFIG_VERSION: u32 = 1;
+
+type Migration = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+/// `MIGRATIONS[n]` upgrades a version `n` document to version `n + 1`
+const MIGRATIONS: [Migration; CONFIG_VERSION as usize] = [v0_to_v1];
+
+/// Schema version of a raw config document; files without one predate versioning
+pub fn document_version(value: &Value) -> u32 {
+    value
+        .get("version")
+        .and_then(Value::as_u64)
+        .map(|v| v as u32)
+        .unwrap_or(0)
+}
+
+/// Bring `value` up to [`CONFIG_VERSION`]; returns the version it started at
+pub fn migrate(value: &mut Value) -> Result<u32, String> {
+    // The earliest layout was a bare list of shortcuts
+    if value.is_array() {
+        let shortcuts = value.take();
+        *value = Value::Object(Map::from_iter([("shortcuts".to_string(), shortcuts)]));
+    }
+
+    let from = document_version(value);
+    if from > CONFIG_VERSION {
+        return Err(format!(
+            "Config version {} was written by a newer release (this one supports up to {})",
+            from, CONFIG_VERSION
+        ));
+    }
+
+    let root = value
+        .as_object_mut()
+        .ok_or("Config must be a JSON object")?;
+    for (version, step) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+        step(root).map_err(|e| format!("Failed to migrate config from version {}: {}", version, e))?;
+        root.insert("version".to_string(), Value::from(version as u32 + 1));
+    }
+
+    Ok(from)
+}
+
+/// Version 0 covers both the original `{name, command}` layout and typed
+/// shortcuts saved before the version field existed.
+fn v0_to_v1(root: &mut Map<String, Value>) -> Result<(), String> {
+    let shortcuts = root
+        .entry("shortcuts")
+        .or_insert_with(|| Value::Array(Vec::new()))
+        .as_array_mut()
+        .ok_or("\"shortcuts\" must be a list")?;
+
+    for (index, entry) in shortcuts.iter_mut().enumerate() {
+        let shortcut = entry
+            .as_object_mut()
+            .ok_or_else(|| format!("shortcut #{} is not an object", index + 1))?;
+
+        // The legacy format only knew executables
+        if !shortcut.contains_key("type") {
+            shortcut.insert("type".to_string(), Value::from("app"));
+        }
+
+        // Keep unnamed entries instead of failing the whole file
+        if !shortcut.get("name").is_some_and(Value::is_string) {
+            let name = shortcut
+                .get("command")
+                .and_then(Value::as_str)
+                .filter(|c| !c.is_empty())
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("Shortcut {}", index + 1));
+            shortcut.insert("name".to_string(), Value::from(name));
+        }
+
+        let has_id = shortcut.get("id").and_then(Value::as_str).is_some_and(|id| !id.is_empty());
+        if !has_id {
+            shortcut.insert("id".to_string(), Value::from(crate::config::new_shortcut_id()));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{parse_config, ShortcutType};
+    use serde_json::json;
+
+    #[test]
+    fn wraps_bare_list() {
+        let mut value = json!([{ "name": "Editor", "command": "code" }]);
+        assert_eq!(migrate(&mut value).unwrap(), 0);
+        assert_eq!(document_version(&value), CONFIG_VERSION);
+        assert_eq!(value["shortcuts"][0]["name"], "Editor");
+        assert_eq!(value["shortcuts"][0]["command"], "code");
+    }
+
+    #[test]
+    fn completes_legacy_entries() {
+        let mut value = json!({ "shortcuts": [
+            { "name": "Editor", "command": "code" },
+            { "command": "notepad.exe" },
+            { "command": "" },
+            { "name": 5, "type": "url", "command": "https://example.com", "id": "kept" },
+        ] });
+        migrate(&mut value).unwrap();
+        let shortcuts = value["shortcuts"].as_array().unwrap();
+
+        let types: Vec<_> = shortcuts.iter().map(|s| s["type"].as_str().unwrap()).collect();
+        assert_eq!(types, ["app", "app", "app", "url"]);
+        let names: Vec<_> = shortcuts.iter().map(|s| s["name"].as_str().unwrap()).collect();
+        assert_eq!(names, ["Editor", "notepad.exe", "Shortcut 3", "https://example.com"]);
+        assert_eq!(shortcuts[3]["id"], "kept");
+        let ids: Vec<_> = shortcuts.iter().map(|s| s["id"].as_str().unwrap()).collect();
+        assert!(ids.iter().all(|id| !id.is_empty()));
+        assert_ne!(ids[0], ids[1]);
+    }
+
+    #[test]
+    fn rejects_newer_version() {
+        let mut value = json!({ "version": CONFIG_VERSION + 1, "shortcuts": [] });
+        assert!(migrate(&mut value).unwrap_err().contains("newer release"));
+        let content = value.to_string();
+        assert!(parse_config(&content).is_err());
+    }
+
+    #[test]
+    fn rejects_what_it_cannot_upgrade() {
+        assert!(migrate(&mut json!("shortcuts")).is_err());
+        assert!(migrate(&mut json!({ "shortcuts": {} })).is_err());
+        assert!(migrate(&mut json!({ "shortcuts": [1] })).is_err());
+    }
+
+    #[test]
+    fn current_version_is_left_alone() {
+        let original = json!({ "version": CONFIG_VERSION, "shortcuts": [{ "id": "x", "name": "N", "type": "folder", "command": "/" }] });
+        let mut value = original.clone();
+        assert_eq!(migrate(&mut value).unwrap(), CONFIG_VERSION);
+        assert_eq!(value, original);
+    }
+
+    #[test]
+    fn upgraded_document_loads() {
+        let content = r#"[
+            { "name": "Editor", "command": "code", "args": "--new-window" },
+            { "command": "calc.exe" }
+        ]"#;
+        let (config, from) = parse_config(content).unwrap();
+        assert_eq!(from, 0);
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.shortcuts.len(), 2);
+        assert_eq!(config.shortcuts[0].name, "Editor");
+        assert_eq!(config.shortcuts[0].shortcut_type, ShortcutType::App);
+        assert_eq!(config.shortcuts[0].args.as_ref().unwrap().to_argv().unwrap(), ["--new-window"]);
+        assert_eq!(config.shortcuts[1].name, "calc.exe");
+        assert!(config.shortcuts.iter().all(|s| !s.id.is_empty()));
+    }
+}
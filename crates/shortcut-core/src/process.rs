@@ -0,0 +1,374 @@
+//! Processes started by shortcuts, from launch until they exit.
+//!
+//! [`ProcessRegistry`] takes the child of every launch it makes and a waiter thread
+//! records how it ended. The list is mirrored to `processes.json` next to the config
+//! so the CLI can show what the tray app has running.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Child;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::backup::write_atomic;
+use crate::config::{get_config_path, Shortcut};
+use crate::launcher::{self, LaunchError, Launcher};
+use crate::logs;
+use crate::sequence::Step;
+
+/// How many finished processes are kept so their exit status can be looked up
+pub const MAX_FINISHED: usize = 20;
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Where a tracked process is in its life
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum ProcessStatus {
+    Running,
+    /// Ended on its own; `code` is None when it was stopped by a signal
+    Exited { code: Option<i32> },
+    /// Stopped through [`ProcessRegistry::cancel`]
+    Killed,
+    /// Stopped after running longer than the shortcut's `timeout_secs`
+    TimedOut,
+}
+
+/// One launch of a shortcut
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProcessInfo {
+    // Unique per launch, unlike PIDs which the OS reuses
+    pub run_id: String,
+    pub pid: u32,
+    pub shortcut_id: String,
+    pub shortcut_name: String,
+    // Local times, e.g. "2024-05-01 14:03:22"
+    pub started: String,
+    pub ended: Option<String>,
+    pub status: ProcessStatus,
+    // Output was captured, see [`crate::logs::read_log`]
+    #[serde(default)]
+    pub has_log: bool,
+    // The inline sequence step that ran, which has no entry in the config under
+    // `shortcut_id`; restarts and notifications go by it instead
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<Shortcut>,
+}
+
+impl ProcessInfo {
+    pub fn is_running(&self) -> bool {
+        self.status == ProcessStatus::Running
+    }
+}
+
+type Listener = Box<dyn Fn() + Send + Sync>;
+type ExitListener = Box<dyn Fn(&ProcessInfo) + Send + Sync>;
+
+#[derive(Default)]
+struct Inner {
+    // Oldest first
+    processes: Mutex<Vec<ProcessInfo>>,
+    // Signalled whenever a process ends
+    ended: Condvar,
+    // Why processes that were asked to stop are stopping, by run ID; they are shown as
+    // running until they have actually exited
+    stopping: Mutex<HashMap<String, ProcessStatus>>,
+    listener: Mutex<Option<Listener>>,
+    exit_listener: Mutex<Option<ExitListener>>,
+}
+
+/// Processes launched by this app. Clones share the same list.
+#[derive(Clone, Default)]
+pub struct ProcessRegistry {
+    inner: Arc<Inner>,
+}
+
+impl ProcessRegistry {
+    /// Create an empty registry, replacing whatever an earlier run left in processes.json
+    pub fn open() -> Self {
+        let registry = Self::default();
+        registry.publish();
+        registry
+    }
+
+    /// Call `f` whenever a process is added, ends or is cleared
+    pub fn on_change(&self, f: impl Fn() + Send + Sync + 'static) {
+        *self.inner.listener.lock().unwrap() = Some(Box::new(f));
+    }
+
+    /// Call `f` with the final state of every process that ends
+    pub fn on_exit(&self, f: impl Fn(&ProcessInfo) + Send + Sync + 'static) {
+        *self.inner.exit_listener.lock().unwrap() = Some(Box::new(f));
+    }
+
+    /// Launch `shortcut` and track its process, capturing the output of hidden runs.
+    /// Visible scripts open in `terminal` on Linux, see [`crate::AppConfig::terminal`].
+    /// Returns None when the launch was handed off to another program and there is
+    /// nothing to track.
+    pub fn launch(&self, shortcut: &Shortcut, terminal: Option<&str>) -> Result<Option<ProcessInfo>, LaunchError> {
+        self.launch_run(shortcut, None, terminal)
+    }
+
+    /// Like [`launch`](Self::launch) for a step of a sequence, keeping an inline step's
+    /// shortcut with the run so it can be restarted
+    pub fn launch_step(&self, step: &Step, terminal: Option<&str>) -> Result<Option<ProcessInfo>, LaunchError> {
+        self.launch_run(&step.shortcut, step.inline.then(|| step.shortcut.clone()), terminal)
+    }
+
+    fn launch_run(&self, shortcut: &Shortcut, step: Option<Shortcut>, terminal: Option<&str>) -> Result<Option<ProcessInfo>, LaunchError> {
+        let run_id = uuid::Uuid::new_v4().to_string();
+        let log = if logs::captures_output(shortcut) {
+            Some(logs::create_log(&run_id).map_err(LaunchError::Failed)?)
+        } else {
+            None
+        };
+        let has_log = log.is_some();
+
+        let options = crate::LaunchOptions { log, terminal: terminal.map(str::to_string) };
+        match crate::launch_with(shortcut, options) {
+            Ok(Some(child)) => Ok(Some(self.track_run(run_id, shortcut, step, child, has_log))),
+            result => {
+                // Nothing ran that could have written to the log
+                logs::remove_log(&run_id);
+                result.map(|_| None)
+            }
+        }
+    }
+
+    /// Track a process started for `shortcut` until it exits
+    pub fn track(&self, shortcut: &Shortcut, child: Child) -> ProcessInfo {
+        self.track_run(uuid::Uuid::new_v4().to_string(), shortcut, None, child, false)
+    }
+
+    fn track_run(&self, run_id: String, shortcut: &Shortcut, step: Option<Shortcut>, mut child: Child, has_log: bool) -> ProcessInfo {
+        let info = ProcessInfo {
+            run_id,
+            pid: child.id(),
+            shortcut_id: shortcut.id.clone(),
+            shortcut_name: shortcut.name.clone(),
+            started: now(),
+            ended: None,
+            status: ProcessStatus::Running,
+            has_log,
+            step,
+        };
+        self.inner.processes.lock().unwrap().push(info.clone());
+        self.publish();
+
+        let (exited_tx, exited_rx) = mpsc::channel::<()>();
+        let registry = self.clone();
+        let run_id = info.run_id.clone();
+        thread::spawn(move || {
+            let code = child.wait().ok().and_then(|status| status.code());
+            let _ = exited_tx.send(());
+            registry.finish(&run_id, ProcessStatus::Exited { code });
+        });
+
+        if let Some(timeout) = shortcut.timeout_secs.filter(|&secs| secs > 0).map(Duration::from_secs) {
+            let registry = self.clone();
+            let run_id = info.run_id.clone();
+            thread::spawn(move || {
+                // Wakes up early when the process exits by itself
+                if exited_rx.recv_timeout(timeout) == Err(RecvTimeoutError::Timeout) {
+                    let _ = registry.stop(&run_id, ProcessStatus::TimedOut);
+                }
+            });
+        }
+        info
+    }
+
+    /// Record that a process has exited with `status`, unless it was stopped through
+    /// [`Self::stop`], which decided how it ends
+    fn finish(&self, run_id: &str, status: ProcessStatus) {
+        let finished = {
+            let mut processes = self.inner.processes.lock().unwrap();
+            // Taken under the list's lock, which stop() holds too, so a stop either comes
+            // in before this or finds the process no longer running
+            let stopped = self.inner.stopping.lock().unwrap().remove(run_id);
+            let finished = processes.iter_mut().find(|p| p.run_id == run_id).map(|process| {
+                process.ended = Some(now());
+                process.status = stopped.unwrap_or(status);
+                process.clone()
+            });
+            prune(&mut processes);
+            finished
+        };
+        self.inner.ended.notify_all();
+        self.publish();
+        if let (Some(process), Some(listener)) = (finished, &*self.inner.exit_listener.lock().unwrap()) {
+            listener(&process);
+        }
+    }
+
+    /// Tracked processes, newest first
+    pub fn list(&self) -> Vec<ProcessInfo> {
+        let mut processes = self.inner.processes.lock().unwrap().clone();
+        processes.reverse();
+        processes
+    }
+
+    /// The process launched as `run_id`
+    pub fn get(&self, run_id: &str) -> Option<ProcessInfo> {
+        self.inner.processes.lock().unwrap().iter().find(|p| p.run_id == run_id).cloned()
+    }
+
+    /// Block until the process launched as `run_id` has ended. Returns its exit code, or
+    /// None when it was stopped or is not tracked.
+    pub fn wait(&self, run_id: &str) -> Option<i32> {
+        let mut processes = self.inner.processes.lock().unwrap();
+        loop {
+            match processes.iter().find(|p| p.run_id == run_id)?.status {
+                ProcessStatus::Running => processes = self.inner.ended.wait(processes).unwrap(),
+                ProcessStatus::Exited { code } => return code,
+                ProcessStatus::Killed | ProcessStatus::TimedOut => return None,
+            }
+        }
+    }
+
+    /// Stop the running process launched as `run_id` and everything it started
+    pub fn cancel(&self, run_id: &str) -> Result<(), String> {
+        self.stop(run_id, ProcessStatus::Killed)
+    }
+
+    /// Kill the process tree of `run_id`, recording `status` as the reason it ends. The
+    /// process stays running in the list until it has exited.
+    fn stop(&self, run_id: &str, status: ProcessStatus) -> Result<(), String> {
+        let processes = self.inner.processes.lock().unwrap();
+        let process = processes
+            .iter()
+            .find(|p| p.run_id == run_id)
+            .ok_or_else(|| format!("No process with run ID {}", run_id))?;
+        if !process.is_running() {
+            return Err(format!("{} is no longer running", process.shortcut_name));
+        }
+        launcher::platform().kill_process(process.pid)?;
+        // Asking again keeps the first reason, e.g. a cancel after the timeout hit
+        self.inner.stopping.lock().unwrap().entry(run_id.to_string()).or_insert(status);
+        Ok(())
+    }
+
+    /// Forget every process that has ended
+    pub fn clear_finished(&self) {
+        self.inner.processes.lock().unwrap().retain(ProcessInfo::is_running);
+        self.publish();
+    }
+
+    /// Mirror the list to processes.json and tell the listener
+    fn publish(&self) {
+        {
+            // Held while writing so an older list can never overwrite a newer one
+            let processes = self.inner.processes.lock().unwrap();
+            // The file is informational only; failing to write it must not break launching
+            if let Ok(json) = serde_json::to_vec_pretty(&*processes) {
+                let _ = write_atomic(&processes_path(), &json);
+            }
+        }
+        if let Some(listener) = &*self.inner.listener.lock().unwrap() {
+            listener();
+        }
+    }
+}
+
+/// Drop the oldest finished processes beyond MAX_FINISHED
+fn prune(processes: &mut Vec<ProcessInfo>) {
+    let finished = processes.iter().filter(|p| !p.is_running()).count();
+    let mut excess = finished.saturating_sub(MAX_FINISHED);
+    processes.retain(|p| {
+        if excess > 0 && !p.is_running() {
+            excess -= 1;
+            false
+        } else {
+            true
+        }
+    });
+}
+
+fn now() -> String {
+    chrono::Local::now().format(TIME_FORMAT).to_string()
+}
+
+/// File the tray app mirrors its process list to
+pub fn processes_path() -> PathBuf {
+    get_config_path().with_file_name("processes.json")
+}
+
+/// Processes tracked by the tray app, newest first, as last written to processes.json.
+///
+/// Entries still marked running whose process is gone (the tray app was killed before
+/// it could record the exit) are reported as exited with an unknown code.
+pub fn read_processes() -> Result<Vec<ProcessInfo>, String> {
+    let path = processes_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut processes: Vec<ProcessInfo> = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+
+    let launcher = launcher::platform();
+    for process in &mut processes {
+        if process.is_running() && !launcher.is_running(process.pid) {
+            process.status = ProcessStatus::Exited { code: None };
+        }
+    }
+    processes.reverse();
+    Ok(processes)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::process::CommandExt;
+    use std::process::Command;
+
+    #[test]
+    fn cancelled_process_is_running_until_it_exits() {
+        // processes.json goes here instead of the real config folder
+        crate::set_config_path(tempfile::tempdir().unwrap().keep().join("config.json"));
+        let child = Command::new("sh")
+            .args(["-c", "trap '' TERM; sleep 30 & wait"])
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let registry = ProcessRegistry::default();
+        let run_id = registry.track(&Shortcut::default(), child).run_id;
+        thread::sleep(Duration::from_millis(200));
+
+        registry.cancel(&run_id).unwrap();
+        assert!(registry.get(&run_id).unwrap().is_running());
+        // Still running, so asking again is fine
+        registry.cancel(&run_id).unwrap();
+
+        assert_eq!(registry.wait(&run_id), None);
+        assert_eq!(registry.get(&run_id).unwrap().status, ProcessStatus::Killed);
+        assert!(registry.cancel(&run_id).is_err());
+    }
+
+    #[test]
+    fn inline_steps_are_kept_with_their_run() {
+        use crate::config::{AppConfig, SequenceStep, ShortcutType};
+
+        crate::set_config_path(tempfile::tempdir().unwrap().keep().join("config.json"));
+        let step = Shortcut { name: "step".to_string(), shortcut_type: ShortcutType::App, command: "true".to_string(), ..Default::default() };
+        let sequence = Shortcut {
+            id: "seq".to_string(),
+            shortcut_type: ShortcutType::Sequence,
+            steps: vec![SequenceStep { shortcut: Some(Box::new(step)), ..Default::default() }],
+            ..Default::default()
+        };
+        let config = AppConfig { shortcuts: vec![sequence.clone()], ..Default::default() };
+        let steps = crate::sequence::resolve(&sequence, &config).unwrap();
+
+        let registry = ProcessRegistry::default();
+        let run = registry.launch_step(&steps[0], None).unwrap().unwrap();
+        assert_eq!(run.shortcut_id, "seq");
+        assert_eq!(run.step.as_ref().map(|s| s.name.as_str()), Some("step"));
+        assert_eq!(registry.wait(&run.run_id), Some(0));
+        assert!(registry.launch(&steps[0].shortcut, None).unwrap().unwrap().step.is_none());
+    }
+}
@@ -0,0 +1,391 @@
+//! Sequence shortcuts: several shortcuts started one after another.
+//!
+//! [`resolve`] looks up every step before anything starts, so a stale ID or a sequence
+//! that includes itself fails the launch as a whole instead of half-way through.
+//! [`run`] then works through the steps, starting each with a [`StepRunner`]: the tray
+//! app tracks them in its [`ProcessRegistry`](crate::ProcessRegistry), the CLI just
+//! launches them.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crate::config::{AppConfig, Shortcut, ShortcutType};
+use crate::launcher::LaunchError;
+use crate::template::{self, Param};
+
+/// Blocks until a started step's process exits and returns its exit code, None when
+/// it was stopped
+pub type Wait = Box<dyn FnOnce() -> Option<i32> + Send>;
+
+/// Starts the steps of a sequence and hears about the ones that fail
+pub trait StepRunner: Send + Sync + 'static {
+    /// Start the shortcut of `step`, returning a way to wait for its process when there is one
+    fn start(&self, step: &Step) -> Result<Option<Wait>, LaunchError>;
+
+    /// `shortcut` could not be started, or exited unsuccessfully while being waited for
+    fn failed(&self, shortcut: &Shortcut, error: &LaunchError);
+}
+
+/// A step with the shortcut it runs, see [`crate::config::SequenceStep`]
+#[derive(Clone, Debug)]
+pub struct Step {
+    pub shortcut: Shortcut,
+    /// `shortcut` is defined in the step itself, so the config has no entry to run again
+    pub inline: bool,
+    /// Resolved steps of `shortcut` when it is a sequence itself
+    pub steps: Vec<Step>,
+    pub wait: bool,
+    pub delay: Duration,
+    pub continue_on_failure: bool,
+    pub parallel: bool,
+}
+
+/// The steps of `sequence`, with referenced shortcuts looked up in `config`
+pub fn resolve(sequence: &Shortcut, config: &AppConfig) -> Result<Vec<Step>, LaunchError> {
+    resolve_in(sequence, config, &mut vec![sequence.id.clone()])
+}
+
+/// `stack` holds the IDs of the sequences being resolved, to catch cycles
+fn resolve_in(sequence: &Shortcut, config: &AppConfig, stack: &mut Vec<String>) -> Result<Vec<Step>, LaunchError> {
+    if sequence.steps.is_empty() {
+        return Err(LaunchError::Invalid(format!("Sequence {} has no steps", sequence.name)));
+    }
+    let mut steps = Vec::new();
+    for (index, step) in sequence.steps.iter().enumerate() {
+        let shortcut = match (&step.shortcut, &step.id) {
+            (Some(inline), _) => {
+                let mut shortcut = (**inline).clone();
+                // Runs of an inline step are listed under the sequence
+                if shortcut.id.is_empty() {
+                    shortcut.id = sequence.id.clone();
+                }
+                shortcut
+            }
+            (None, Some(id)) => {
+                if stack.contains(id) {
+                    return Err(LaunchError::Invalid(format!("Sequence {} includes itself", sequence.name)));
+                }
+                config
+                    .find(id)
+                    .cloned()
+                    .ok_or_else(|| LaunchError::Invalid(format!("Shortcut not found: {}", id)))?
+            }
+            (None, None) => {
+                return Err(LaunchError::Invalid(format!(
+                    "Step {} of {} has neither an id nor a shortcut",
+                    index + 1,
+                    sequence.name
+                )))
+            }
+        };
+        let nested = if shortcut.shortcut_type == ShortcutType::Sequence {
+            stack.push(shortcut.id.clone());
+            let nested = resolve_in(&shortcut, config, stack)?;
+            stack.pop();
+            nested
+        } else {
+            Vec::new()
+        };
+        steps.push(Step {
+            shortcut,
+            inline: step.shortcut.is_some(),
+            steps: nested,
+            wait: step.wait,
+            delay: Duration::from_millis(step.delay_ms),
+            continue_on_failure: step.continue_on_failure,
+            parallel: step.parallel,
+        });
+    }
+    Ok(steps)
+}
+
+/// Add the placeholders of every step to `params`, see [`template::params`]
+pub(crate) fn collect_params(steps: &[Step], params: &mut Vec<Param>) {
+    for step in steps {
+        template::collect_params(&step.shortcut, params);
+        collect_params(&step.steps, params);
+    }
+}
+
+/// `steps` with their placeholders filled in from `values`, see [`template::fill`]
+pub fn fill(steps: &[Step], values: &BTreeMap<String, String>) -> Result<Vec<Step>, LaunchError> {
+    steps
+        .iter()
+        .map(|step| {
+            Ok(Step {
+                shortcut: template::fill(&step.shortcut, values)?,
+                steps: fill(&step.steps, values)?,
+                ..step.clone()
+            })
+        })
+        .collect()
+}
+
+/// Run `steps` in order on the calling thread. Stops at the first failed step that does
+/// not allow continuing, and returns whether none did. Parallel steps run on threads of
+/// their own, are waited for before returning, and never stop the sequence.
+pub fn run<R: StepRunner>(steps: &[Step], runner: &Arc<R>) -> bool {
+    let mut parallel = Vec::new();
+    let mut ok = true;
+    for step in steps {
+        if step.parallel {
+            let (step, runner) = (step.clone(), runner.clone());
+            parallel.push(thread::spawn(move || run_step(&step, &runner)));
+        } else if !run_step(step, runner) && !step.continue_on_failure {
+            ok = false;
+            break;
+        }
+    }
+    for handle in parallel {
+        let _ = handle.join();
+    }
+    ok
+}
+
+/// Run one step, reporting a failure to `runner`; returns whether it succeeded
+fn run_step<R: StepRunner>(step: &Step, runner: &Arc<R>) -> bool {
+    thread::sleep(step.delay);
+    if step.shortcut.shortcut_type == ShortcutType::Sequence {
+        // Failures inside were reported by the nested run
+        return run(&step.steps, runner);
+    }
+
+    let name = &step.shortcut.name;
+    let result = runner.start(step).and_then(|wait| match wait {
+        Some(wait) if step.wait => match wait() {
+            Some(0) => Ok(()),
+            Some(code) => Err(LaunchError::Failed(format!("{} exited with code {}", name, code))),
+            None => Err(LaunchError::Failed(format!("{} was stopped", name))),
+        },
+        _ => Ok(()),
+    });
+    if let Err(e) = &result {
+        runner.failed(&step.shortcut, e);
+    }
+    result.is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SequenceStep;
+    use std::sync::Mutex;
+
+    fn app(id: &str) -> Shortcut {
+        Shortcut {
+            id: id.to_string(),
+            name: id.to_string(),
+            shortcut_type: ShortcutType::App,
+            command: id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn sequence(id: &str, steps: Vec<SequenceStep>) -> Shortcut {
+        Shortcut { shortcut_type: ShortcutType::Sequence, steps, ..app(id) }
+    }
+
+    fn by_id(id: &str) -> SequenceStep {
+        SequenceStep { id: Some(id.to_string()), ..Default::default() }
+    }
+
+    fn config(shortcuts: Vec<Shortcut>) -> AppConfig {
+        AppConfig { shortcuts, ..Default::default() }
+    }
+
+    fn resolve_id(config: &AppConfig, id: &str) -> Result<Vec<Step>, LaunchError> {
+        resolve(config.find(id).unwrap(), config)
+    }
+
+    fn error(result: Result<Vec<Step>, LaunchError>) -> String {
+        result.unwrap_err().to_string()
+    }
+
+    #[test]
+    fn resolves_nested_sequences() {
+        let config = config(vec![
+            app("a"),
+            app("b"),
+            sequence("inner", vec![by_id("b")]),
+            sequence("outer", vec![by_id("a"), SequenceStep { delay_ms: 50, ..by_id("inner") }, by_id("a")]),
+        ]);
+        let steps = resolve_id(&config, "outer").unwrap();
+        let names: Vec<_> = steps.iter().map(|s| s.shortcut.name.as_str()).collect();
+        assert_eq!(names, ["a", "inner", "a"]);
+        assert_eq!(steps[1].delay, Duration::from_millis(50));
+        assert_eq!(steps[1].steps.len(), 1);
+        assert_eq!(steps[1].steps[0].shortcut.name, "b");
+    }
+
+    #[test]
+    fn shared_steps_are_not_cycles() {
+        // outer -> left -> a and outer -> right -> left -> a
+        let config = config(vec![
+            app("a"),
+            sequence("left", vec![by_id("a")]),
+            sequence("right", vec![by_id("left")]),
+            sequence("outer", vec![by_id("left"), by_id("right")]),
+        ]);
+        assert!(resolve_id(&config, "outer").is_ok());
+    }
+
+    #[test]
+    fn rejects_cycles() {
+        let config = config(vec![
+            sequence("self", vec![by_id("self")]),
+            sequence("a", vec![by_id("b")]),
+            sequence("b", vec![by_id("a")]),
+        ]);
+        assert!(error(resolve_id(&config, "self")).contains("includes itself"));
+        assert!(error(resolve_id(&config, "a")).contains("includes itself"));
+        assert!(error(resolve_id(&config, "b")).contains("includes itself"));
+    }
+
+    #[test]
+    fn rejects_broken_steps() {
+        let config = config(vec![
+            app("a"),
+            sequence("unknown", vec![by_id("a"), by_id("nope")]),
+            sequence("empty", Vec::new()),
+            sequence("contains_empty", vec![by_id("empty")]),
+            sequence("blank", vec![SequenceStep::default()]),
+        ]);
+        assert_eq!(error(resolve_id(&config, "unknown")), "Shortcut not found: nope");
+        assert!(error(resolve_id(&config, "empty")).contains("has no steps"));
+        assert!(error(resolve_id(&config, "contains_empty")).contains("has no steps"));
+        assert!(error(resolve_id(&config, "blank")).contains("neither an id nor a shortcut"));
+    }
+
+    #[test]
+    fn inline_steps_run_under_the_sequence() {
+        let inline = |id: &str| SequenceStep { shortcut: Some(Box::new(Shortcut { id: id.to_string(), ..app("inline") })), ..Default::default() };
+        let config = config(vec![sequence("seq", vec![inline(""), inline("own")])]);
+        let steps = resolve_id(&config, "seq").unwrap();
+        assert_eq!(steps[0].shortcut.id, "seq");
+        assert_eq!(steps[1].shortcut.id, "own");
+        assert!(steps.iter().all(|step| step.inline));
+    }
+
+    /// Records what happens to each step. A step's command decides how it behaves:
+    /// `unstartable`, `fail` (exits with 1), `slow` or anything else (exits with 0).
+    #[derive(Default)]
+    struct Recorder {
+        events: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Recorder {
+        fn events(&self) -> Vec<String> {
+            self.events.lock().unwrap().clone()
+        }
+
+        fn position(&self, event: &str) -> usize {
+            self.events().iter().position(|e| e == event).unwrap_or_else(|| panic!("no {:?} in {:?}", event, self.events()))
+        }
+    }
+
+    impl StepRunner for Recorder {
+        fn start(&self, step: &Step) -> Result<Option<Wait>, LaunchError> {
+            let shortcut = &step.shortcut;
+            self.events.lock().unwrap().push(format!("start {}", shortcut.name));
+            let (events, name) = (self.events.clone(), shortcut.name.clone());
+            let code = match shortcut.command.as_str() {
+                "unstartable" => return Err(LaunchError::NotFound(name)),
+                "fail" => 1,
+                _ => 0,
+            };
+            let slow = shortcut.command == "slow";
+            Ok(Some(Box::new(move || {
+                if slow {
+                    thread::sleep(Duration::from_millis(300));
+                }
+                events.lock().unwrap().push(format!("end {}", name));
+                Some(code)
+            })))
+        }
+
+        fn failed(&self, shortcut: &Shortcut, _: &LaunchError) {
+            self.events.lock().unwrap().push(format!("failed {}", shortcut.name));
+        }
+    }
+
+    fn step(name: &str, command: &str) -> Step {
+        Step {
+            shortcut: Shortcut { name: name.to_string(), command: command.to_string(), ..Default::default() },
+            inline: false,
+            steps: Vec::new(),
+            wait: true,
+            delay: Duration::ZERO,
+            continue_on_failure: false,
+            parallel: false,
+        }
+    }
+
+    fn events(events: &[&str]) -> Vec<String> {
+        events.iter().map(|e| e.to_string()).collect()
+    }
+
+    #[test]
+    fn stops_at_failed_step() {
+        let runner = Arc::new(Recorder::default());
+        assert!(!run(&[step("a", "ok"), step("b", "fail"), step("c", "ok")], &runner));
+        assert_eq!(runner.events(), events(&["start a", "end a", "start b", "end b", "failed b"]));
+
+        let runner = Arc::new(Recorder::default());
+        assert!(!run(&[step("a", "unstartable"), step("b", "ok")], &runner));
+        assert_eq!(runner.events(), events(&["start a", "failed a"]));
+    }
+
+    #[test]
+    fn continues_when_allowed() {
+        let runner = Arc::new(Recorder::default());
+        let steps = [step("a", "fail"), Step { continue_on_failure: true, ..step("b", "unstartable") }, step("c", "ok")];
+        // a fails without continue_on_failure
+        assert!(!run(&steps, &runner));
+
+        let runner = Arc::new(Recorder::default());
+        let steps = [Step { continue_on_failure: true, ..step("a", "fail") }, Step { continue_on_failure: true, ..step("b", "unstartable") }, step("c", "ok")];
+        assert!(run(&steps, &runner));
+        assert_eq!(
+            runner.events(),
+            events(&["start a", "end a", "failed a", "start b", "failed b", "start c", "end c"])
+        );
+    }
+
+    #[test]
+    fn exit_codes_only_count_when_waited_for() {
+        let runner = Arc::new(Recorder::default());
+        assert!(run(&[Step { wait: false, ..step("a", "fail") }, step("b", "ok")], &runner));
+        assert_eq!(runner.events(), events(&["start a", "start b", "end b"]));
+    }
+
+    #[test]
+    fn parallel_steps_run_alongside_and_are_waited_for() {
+        let runner = Arc::new(Recorder::default());
+        let steps = [Step { parallel: true, ..step("slow", "slow") }, step("next", "ok")];
+        assert!(run(&steps, &runner));
+        assert!(runner.position("end next") < runner.position("end slow"));
+    }
+
+    #[test]
+    fn failed_parallel_step_does_not_stop_the_sequence() {
+        let runner = Arc::new(Recorder::default());
+        let steps = [Step { parallel: true, ..step("a", "fail") }, step("b", "slow")];
+        assert!(run(&steps, &runner));
+        runner.position("failed a");
+        runner.position("end b");
+    }
+
+    #[test]
+    fn nested_sequence_failure_stops_the_outer_one() {
+        let runner = Arc::new(Recorder::default());
+        let inner = Step {
+            shortcut: Shortcut { name: "inner".to_string(), shortcut_type: ShortcutType::Sequence, ..Default::default() },
+            steps: vec![step("a", "fail")],
+            ..step("inner", "")
+        };
+        assert!(!run(&[inner, step("b", "ok")], &runner));
+        assert_eq!(runner.events(), events(&["start a", "end a", "failed a"]));
+    }
+}
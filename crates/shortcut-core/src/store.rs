@@ -0,0 +1,177 @@
+//! Shared in-memory config with a single, locked write path.
+//!
+//! The tray app and the CLI both go through [`ConfigStore`]. Every write takes an
+//! exclusive lock on `config.json.lock` next to the config, re-reads the file, applies
+//! the change and saves it, so neither process can overwrite the other's updates.
+
+use std::fs::{self, File, OpenOptions};
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::backup::read_backup;
+use crate::config::{get_config_path, load_config, read_config_file, save_config, AppConfig, ConfigError};
+
+/// Exclusive lock on the config file, shared between processes; released on drop
+pub struct ConfigLock {
+    _file: File,
+}
+
+impl ConfigLock {
+    /// Block until no other process is reading or writing the config
+    pub fn acquire() -> Result<Self, String> {
+        // Lock a sidecar file: config.json itself is replaced on every save
+        let mut path = get_config_path().into_os_string();
+        path.push(".lock");
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open config lock: {}", e))?;
+        file.lock()
+            .map_err(|e| format!("Failed to lock config: {}", e))?;
+        Ok(Self { _file: file })
+    }
+}
+
+#[derive(Default)]
+struct StoreState {
+    // Last config that loaded successfully
+    config: Option<AppConfig>,
+    // Why the most recent load failed, cleared by the next successful one
+    error: Option<ConfigError>,
+}
+
+/// The config held in memory, kept in sync with config.json
+#[derive(Default)]
+pub struct ConfigStore {
+    state: Mutex<StoreState>,
+}
+
+impl ConfigStore {
+    /// Create a store and load the config from disk
+    pub fn open() -> Self {
+        let store = Self::default();
+        let _ = store.reload();
+        store
+    }
+
+    /// Re-read config.json. On failure the last good config is kept and the error is remembered.
+    pub fn reload(&self) -> Result<AppConfig, ConfigError> {
+        let mut state = self.state.lock().unwrap();
+        let loaded = match ConfigLock::acquire() {
+            // Loading may write the file (defaults, migrations), so it happens under the lock
+            Ok(_lock) => load_config(),
+            Err(e) => Err(ConfigError::from(e)),
+        };
+        match loaded {
+            Ok(config) => {
+                state.config = Some(config.clone());
+                state.error = None;
+                Ok(config)
+            }
+            Err(e) => {
+                state.error = Some(e.clone());
+                Err(e)
+            }
+        }
+    }
+
+    /// The in-memory config, or the load error if nothing has loaded yet
+    pub fn snapshot(&self) -> Result<AppConfig, ConfigError> {
+        let state = self.state.lock().unwrap();
+        match (&state.config, &state.error) {
+            (Some(config), _) => Ok(config.clone()),
+            (None, Some(e)) => Err(e.clone()),
+            (None, None) => Err(ConfigError::from("Config has not been loaded".to_string())),
+        }
+    }
+
+    /// Why the last load failed, if it did
+    pub fn error(&self) -> Option<ConfigError> {
+        self.state.lock().unwrap().error.clone()
+    }
+
+    /// Apply `f` to the current config and save it.
+    ///
+    /// The file is re-read under the lock, so changes made by another process since
+    /// our last load are kept. Nothing is written if the file on disk is broken.
+    pub fn update<T>(&self, f: impl FnOnce(&mut AppConfig) -> Result<T, String>) -> Result<T, String> {
+        let mut state = self.state.lock().unwrap();
+        let _lock = ConfigLock::acquire()?;
+        let mut config = match load_config() {
+            Ok(config) => config,
+            Err(e) => {
+                state.error = Some(e.clone());
+                return Err(e.into());
+            }
+        };
+        let result = f(&mut config)?;
+        save_config(&config)?;
+        state.config = Some(config);
+        state.error = None;
+        Ok(result)
+    }
+
+    /// Replace the whole config, even if the current file is broken
+    pub fn replace(&self, config: AppConfig) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        let _lock = ConfigLock::acquire()?;
+        save_config(&config)?;
+        state.config = Some(config);
+        state.error = None;
+        Ok(())
+    }
+
+    /// Replace the config with the one stored at `path`
+    pub fn import(&self, path: &Path) -> Result<(), String> {
+        self.replace(read_config_file(path)?)
+    }
+
+    /// Copy the current config file to `path`
+    pub fn export(&self, path: &Path) -> Result<(), String> {
+        let _lock = ConfigLock::acquire()?;
+        fs::copy(get_config_path(), path)
+            .map_err(|e| format!("Failed to export config: {}", e))?;
+        Ok(())
+    }
+
+    /// Replace the config with the backup called `name`; the current config is backed up first
+    pub fn restore_backup(&self, name: &str) -> Result<(), String> {
+        self.replace(read_backup(name)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::lock_test_config;
+
+    #[test]
+    fn update_leaves_a_broken_file_alone() {
+        crate::set_config_path(tempfile::tempdir().unwrap().keep().join("config.json"));
+        let _lock = lock_test_config();
+        let store = ConfigStore::default();
+        store.replace(AppConfig { terminal: Some("good".to_string()), ..Default::default() }).unwrap();
+
+        let broken = "{ \"shortcuts\": [ oops";
+        fs::write(get_config_path(), broken).unwrap();
+        let mut called = false;
+        assert!(store
+            .update(|_| {
+                called = true;
+                Ok(())
+            })
+            .is_err());
+        assert!(!called);
+        assert_eq!(fs::read_to_string(get_config_path()).unwrap(), broken);
+        // The last good config stays available, with the error alongside it
+        assert!(store.error().is_some());
+        assert_eq!(store.snapshot().unwrap().terminal.as_deref(), Some("good"));
+
+        // Replacing is how a broken file gets fixed
+        store.replace(AppConfig::default()).unwrap();
+        assert!(store.update(|config| Ok(config.shortcuts.len())).is_ok());
+        assert!(store.error().is_none());
+    }
+}
@@ -0,0 +1,375 @@
+//! Placeholders that are filled in when a shortcut is launched.
+//!
+//! `command`, `args`, `script` and `working_dir` may contain `{{name}}`,
+//! `{{name=default}}` or `{{name:choice(a,b,c)}}` (optionally followed by `=default`).
+//! Front-ends ask for the [`params`] before launching and pass the answers to [`fill`].
+//! The values last used for each shortcut are kept in `last_values.json` next to the
+//! config so they can be offered again.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::ops::Range;
+use std::path::PathBuf;
+
+use crate::backup::write_atomic;
+use crate::config::{get_config_path, AppConfig, CommandArgs, Shortcut, ShortcutType};
+use crate::launcher::LaunchError;
+
+/// A value asked for before launching
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Param {
+    pub name: String,
+    // Used when no value is given
+    pub default: Option<String>,
+    // Allowed values; empty allows any
+    pub choices: Vec<String>,
+    // Value given on the previous launch, see [`last_values`]
+    pub last: Option<String>,
+}
+
+/// A `{{...}}` placeholder and where it is in its text
+struct Placeholder {
+    range: Range<usize>,
+    param: Param,
+}
+
+/// Every placeholder in `text`. Braces that do not form a valid placeholder are left alone.
+fn placeholders(text: &str) -> Vec<Placeholder> {
+    let mut found = Vec::new();
+    let mut from = 0;
+    while let Some(start) = text[from..].find("{{").map(|i| from + i) {
+        let Some(end) = text[start + 2..].find("}}").map(|i| start + 2 + i) else {
+            break;
+        };
+        match parse(&text[start + 2..end]) {
+            Some(param) => {
+                found.push(Placeholder { range: start..end + 2, param });
+                from = end + 2;
+            }
+            None => from = start + 2,
+        }
+    }
+    found
+}
+
+/// Parse the inside of a placeholder: `name[:choice(a,b)][=default]`
+fn parse(inner: &str) -> Option<Param> {
+    let inner = inner.trim();
+    let name_len = inner
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.'))
+        .unwrap_or(inner.len());
+    // Names start like identifiers, which leaves e.g. Go's `{{.Field}}` alone
+    if !inner.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_') {
+        return None;
+    }
+    let (name, mut rest) = inner.split_at(name_len);
+
+    let mut choices = Vec::new();
+    if let Some(list) = rest.strip_prefix(":choice(") {
+        let close = list.find(')')?;
+        choices = list[..close]
+            .split(',')
+            .map(|choice| choice.trim().to_string())
+            .filter(|choice| !choice.is_empty())
+            .collect();
+        rest = &list[close + 1..];
+    }
+    let default = match rest.trim_start() {
+        "" => None,
+        rest => Some(rest.strip_prefix('=')?.to_string()),
+    };
+    Some(Param { name: name.to_string(), default, choices, last: None })
+}
+
+/// Text fields of `shortcut` that may hold placeholders. `args` given as one line is
+/// included as-is.
+fn fields(shortcut: &Shortcut) -> Vec<&str> {
+    let mut fields = vec![shortcut.command.as_str()];
+    match &shortcut.args {
+        Some(CommandArgs::Line(line)) => fields.push(line),
+        Some(CommandArgs::List(list)) => fields.extend(list.iter().map(String::as_str)),
+        None => {}
+    }
+    fields.extend(shortcut.script.as_deref());
+    fields.extend(shortcut.working_dir.as_deref());
+    fields
+}
+
+/// Values `shortcut` asks for, in the order they first appear, with the values last used
+/// for it. A sequence asks for those of all its steps.
+pub fn params(shortcut: &Shortcut, config: &AppConfig) -> Result<Vec<Param>, LaunchError> {
+    let mut params = Vec::new();
+    if shortcut.shortcut_type == ShortcutType::Sequence {
+        crate::sequence::collect_params(&crate::sequence::resolve(shortcut, config)?, &mut params);
+    } else {
+        collect_params(shortcut, &mut params);
+    }
+
+    let last = last_values(&shortcut.id);
+    for param in &mut params {
+        param.last = last.get(&param.name).cloned();
+    }
+    Ok(params)
+}
+
+/// Add the placeholders of `shortcut` to `params`
+pub(crate) fn collect_params(shortcut: &Shortcut, params: &mut Vec<Param>) {
+    for field in fields(shortcut) {
+        merge_params(params, placeholders(field).into_iter().map(|p| p.param));
+    }
+}
+
+/// Add `more` to `params`. A name that is already there keeps its first default and
+/// list of choices, filling in only what it lacked.
+fn merge_params(params: &mut Vec<Param>, more: impl IntoIterator<Item = Param>) {
+    for param in more {
+        match params.iter_mut().find(|p| p.name == param.name) {
+            Some(existing) => {
+                if existing.default.is_none() {
+                    existing.default = param.default;
+                }
+                if existing.choices.is_empty() {
+                    existing.choices = param.choices;
+                }
+            }
+            None => params.push(param),
+        }
+    }
+}
+
+/// A copy of `shortcut` with every placeholder replaced by its value from `values`, or
+/// its default. A one-line `args` is split first, so a value with spaces stays one argument.
+pub fn fill(shortcut: &Shortcut, values: &BTreeMap<String, String>) -> Result<Shortcut, LaunchError> {
+    let mut filled = shortcut.clone();
+    filled.command = fill_text(&shortcut.command, values)?;
+    filled.args = match &shortcut.args {
+        Some(CommandArgs::Line(line)) if !placeholders(line).is_empty() => Some(CommandArgs::List(fill_line(line, values)?)),
+        Some(CommandArgs::List(list)) => Some(CommandArgs::List(
+            list.iter().map(|arg| fill_text(arg, values)).collect::<Result<_, _>>()?,
+        )),
+        args => args.clone(),
+    };
+    filled.script = shortcut.script.as_deref().map(|s| fill_text(s, values)).transpose()?;
+    filled.working_dir = shortcut.working_dir.as_deref().map(|d| fill_text(d, values)).transpose()?;
+    Ok(filled)
+}
+
+/// The value for `param`: the given one, else its default
+fn value_for(param: &Param, values: &BTreeMap<String, String>) -> Result<String, LaunchError> {
+    let value = values
+        .get(&param.name)
+        .or(param.default.as_ref())
+        .ok_or_else(|| LaunchError::Invalid(format!("No value given for {}", param.name)))?;
+    if !param.choices.is_empty() && !param.choices.contains(value) {
+        return Err(LaunchError::Invalid(format!(
+            "{} is not a valid {}; expected one of {}",
+            value,
+            param.name,
+            param.choices.join(", ")
+        )));
+    }
+    Ok(value.clone())
+}
+
+fn fill_text(text: &str, values: &BTreeMap<String, String>) -> Result<String, LaunchError> {
+    let mut filled = String::with_capacity(text.len());
+    let mut last = 0;
+    for placeholder in placeholders(text) {
+        filled.push_str(&text[last..placeholder.range.start]);
+        filled.push_str(&value_for(&placeholder.param, values)?);
+        last = placeholder.range.end;
+    }
+    filled.push_str(&text[last..]);
+    Ok(filled)
+}
+
+// Stand-in for the n-th placeholder while a line is split; private-use characters that
+// no quoting rule treats specially
+const MARK_START: char = '\u{E000}';
+const MARK_END: char = '\u{E001}';
+
+/// Split `line` into arguments with the placeholders still in, then fill each argument
+fn fill_line(line: &str, values: &BTreeMap<String, String>) -> Result<Vec<String>, LaunchError> {
+    let found = placeholders(line);
+    let mut marked = String::with_capacity(line.len());
+    let mut last = 0;
+    for (index, placeholder) in found.iter().enumerate() {
+        marked.push_str(&line[last..placeholder.range.start]);
+        marked.push_str(&format!("{}{}{}", MARK_START, index, MARK_END));
+        last = placeholder.range.end;
+    }
+    marked.push_str(&line[last..]);
+
+    let filled: Vec<String> = found
+        .iter()
+        .map(|placeholder| value_for(&placeholder.param, values))
+        .collect::<Result<_, _>>()?;
+    let args = crate::args::split_args(&marked).map_err(LaunchError::Invalid)?;
+    Ok(args
+        .into_iter()
+        .map(|arg| {
+            let mut out = String::with_capacity(arg.len());
+            let mut rest = arg.as_str();
+            while let Some(start) = rest.find(MARK_START) {
+                let Some(end) = rest[start..].find(MARK_END).map(|i| start + i) else {
+                    break;
+                };
+                let index = rest[start + MARK_START.len_utf8()..end].parse::<usize>().ok();
+                match index.and_then(|index| filled.get(index)) {
+                    Some(value) => {
+                        out.push_str(&rest[..start]);
+                        out.push_str(value);
+                    }
+                    // The line itself held something that looks like a mark; keep it as written
+                    None => out.push_str(&rest[..end + MARK_END.len_utf8()]),
+                }
+                rest = &rest[end + MARK_END.len_utf8()..];
+            }
+            out.push_str(rest);
+            out
+        })
+        .collect())
+}
+
+/// File the values last used for each shortcut are kept in
+pub fn last_values_path() -> PathBuf {
+    get_config_path().with_file_name("last_values.json")
+}
+
+fn read_last_values() -> BTreeMap<String, BTreeMap<String, String>> {
+    fs::read_to_string(last_values_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Values last used for the shortcut with ID `id`
+pub fn last_values(id: &str) -> BTreeMap<String, String> {
+    read_last_values().remove(id).unwrap_or_default()
+}
+
+/// Remember `values` as the last ones used for the shortcut with ID `id`
+pub fn remember(id: &str, values: &BTreeMap<String, String>) -> Result<(), String> {
+    let mut all = read_last_values();
+    all.insert(id.to_string(), values.clone());
+    let json = serde_json::to_vec_pretty(&all)
+        .map_err(|e| format!("Failed to serialize last values: {}", e))?;
+    write_atomic(&last_values_path(), &json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn param(name: &str, default: Option<&str>, choices: &[&str]) -> Param {
+        Param {
+            name: name.to_string(),
+            default: default.map(str::to_string),
+            choices: choices.iter().map(|c| c.to_string()).collect(),
+            last: None,
+        }
+    }
+
+    fn values(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn found(text: &str) -> Vec<Param> {
+        placeholders(text).into_iter().map(|p| p.param).collect()
+    }
+
+    #[test]
+    fn parses_placeholders() {
+        assert_eq!(found("{{ticket}}"), [param("ticket", None, &[])]);
+        assert_eq!(found("{{ branch=main }}"), [param("branch", Some("main"), &[])]);
+        assert_eq!(found("{{msg=}}"), [param("msg", Some(""), &[])]);
+        assert_eq!(found("{{env:choice(dev, staging,prod)}}"), [param("env", None, &["dev", "staging", "prod"])]);
+        assert_eq!(found("{{env:choice(a,b)=a}}"), [param("env", Some("a"), &["a", "b"])]);
+        assert_eq!(
+            found("deploy {{app.name}} to {{env-1}}"),
+            [param("app.name", None, &[]), param("env-1", None, &[])]
+        );
+    }
+
+    #[test]
+    fn leaves_other_braces_alone() {
+        for text in ["{{.Field}}", "{{ json . }}", "{{}}", "{{ticket", "{ticket}", "{{a b}}", "{{env:choice(a}}", "{{x:other}}"] {
+            assert!(found(text).is_empty(), "found a placeholder in {:?}", text);
+        }
+        let shortcut = Shortcut {
+            command: "docker".to_string(),
+            args: Some(CommandArgs::Line("inspect --format '{{ json .State }}' {{name}}".to_string())),
+            ..Default::default()
+        };
+        let filled = fill(&shortcut, &values(&[("name", "db")])).unwrap();
+        assert_eq!(
+            filled.args,
+            Some(CommandArgs::List(vec!["inspect".into(), "--format".into(), "{{ json .State }}".into(), "db".into()]))
+        );
+    }
+
+    #[test]
+    fn merges_repeated_names() {
+        let shortcut = Shortcut {
+            command: "{{env}}".to_string(),
+            script: Some("{{env:choice(a,b)=b}} {{other}}".to_string()),
+            ..Default::default()
+        };
+        let mut params = Vec::new();
+        collect_params(&shortcut, &mut params);
+        assert_eq!(params, [param("env", Some("b"), &["a", "b"]), param("other", None, &[])]);
+    }
+
+    #[test]
+    fn value_with_spaces_stays_one_argument() {
+        let shortcut = Shortcut {
+            command: "git".to_string(),
+            args: Some(CommandArgs::Line("commit -m {{msg}} --author=\"{{who}}\" '{{tag}}'".to_string())),
+            ..Default::default()
+        };
+        let filled = fill(&shortcut, &values(&[("msg", "fix the \"thing\"; rm -rf ~"), ("who", "A B"), ("tag", "it's")])).unwrap();
+        assert_eq!(
+            filled.args.unwrap().to_argv().unwrap(),
+            ["commit", "-m", "fix the \"thing\"; rm -rf ~", "--author=A B", "it's"]
+        );
+    }
+
+    #[test]
+    fn text_that_looks_like_a_mark_is_kept() {
+        let line = format!("{{{{a}}}} {}7{}", MARK_START, MARK_END);
+        let shortcut = Shortcut { command: "echo".to_string(), args: Some(CommandArgs::Line(line)), ..Default::default() };
+        let filled = fill(&shortcut, &values(&[("a", "one")])).unwrap();
+        assert_eq!(
+            filled.args.unwrap().to_argv().unwrap(),
+            ["one".to_string(), format!("{}7{}", MARK_START, MARK_END)]
+        );
+    }
+
+    #[test]
+    fn fills_every_field_with_values_or_defaults() {
+        let shortcut = Shortcut {
+            command: "/opt/{{app}}/bin".to_string(),
+            args: Some(CommandArgs::List(vec!["--env={{env:choice(dev,prod)=dev}}".to_string()])),
+            script: Some("echo {{app}}".to_string()),
+            working_dir: Some("/srv/{{app}}".to_string()),
+            ..Default::default()
+        };
+        let filled = fill(&shortcut, &values(&[("app", "web")])).unwrap();
+        assert_eq!(filled.command, "/opt/web/bin");
+        assert_eq!(filled.args, Some(CommandArgs::List(vec!["--env=dev".to_string()])));
+        assert_eq!(filled.script.as_deref(), Some("echo web"));
+        assert_eq!(filled.working_dir.as_deref(), Some("/srv/web"));
+    }
+
+    #[test]
+    fn rejects_missing_and_invalid_values() {
+        let shortcut = Shortcut { command: "{{env:choice(dev,prod)}}".to_string(), ..Default::default() };
+        assert_eq!(fill(&shortcut, &values(&[("env", "prod")])).unwrap().command, "prod");
+        assert!(matches!(fill(&shortcut, &values(&[("env", "qa")])), Err(LaunchError::Invalid(_))));
+        assert!(matches!(fill(&shortcut, &values(&[])), Err(LaunchError::Invalid(_))));
+
+        let line = Shortcut { args: Some(CommandArgs::Line("{{env:choice(dev,prod)}}".to_string())), ..Default::default() };
+        assert!(fill(&line, &values(&[("env", "dev; reboot")])).is_err());
+    }
+}
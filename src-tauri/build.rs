@@ -1,3 +1,40 @@
+// Every command of the app; declaring them makes each one need a permission, which the
+// files in capabilities/ grant per window
+const COMMANDS: &[&str] = &[
+    "get_shortcuts",
+    "get_config_error",
+    "reload_config",
+    "add_shortcut",
+    "update_shortcut",
+    "shortcut_warnings",
+    "delete_shortcut",
+    "reorder_shortcut",
+    "shortcut_params",
+    "launch_shortcut_by_id",
+    "list_processes",
+    "cancel_process",
+    "restart_process",
+    "clear_finished_processes",
+    "list_logs",
+    "read_log",
+    "hide_window",
+    "open_settings",
+    "exit_app",
+    "get_autostart",
+    "set_autostart",
+    "resize_main_window",
+    "export_config",
+    "import_config",
+    "get_config_location",
+    "get_terminal",
+    "set_terminal",
+    "list_backups",
+    "restore_backup",
+];
+
 fn main() {
-    tauri_build::build()
+    tauri_build::try_build(
+        tauri_build::Attributes::new().app_manifest(tauri_build::AppManifest::new().commands(COMMANDS)),
+    )
+    .expect("failed to run tauri-build")
 }
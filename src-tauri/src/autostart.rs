@@ -0,0 +1,88 @@
+//! Start-on-login support: the HKCU Run key on Windows, an XDG autostart entry on Linux.
+
+#[cfg(target_os = "windows")]
+mod imp {
+    use winreg::enums::*;
+    use winreg::RegKey;
+
+    const AUTOSTART_KEY: &str = "Shortcuts";
+    const RUN_KEY_PATH: &str = r"Software\Microsoft\Windows\CurrentVersion\Run";
+
+    pub fn is_enabled() -> bool {
+        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+        if let Ok(run_key) = hkcu.open_subkey(RUN_KEY_PATH) {
+            run_key.get_value::<String, _>(AUTOSTART_KEY).is_ok()
+        } else {
+            false
+        }
+    }
+
+    pub fn set_enabled(enabled: bool) -> Result<(), String> {
+        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+        let run_key = hkcu.open_subkey_with_flags(RUN_KEY_PATH, KEY_WRITE)
+            .map_err(|e| format!("Failed to open registry key: {}", e))?;
+
+        if enabled {
+            // Get the current executable path
+            let exe_path = std::env::current_exe()
+                .map_err(|e| format!("Failed to get executable path: {}", e))?;
+            let exe_str = exe_path.to_string_lossy().to_string();
+
+            run_key.set_value(AUTOSTART_KEY, &exe_str)
+                .map_err(|e| format!("Failed to set registry value: {}", e))?;
+        } else {
+            // Remove the registry value (ignore error if it doesn't exist)
+            let _ = run_key.delete_value(AUTOSTART_KEY);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use std::fs;
+    use std::path::PathBuf;
+
+    const DESKTOP_FILE: &str = "shortcut-rs.desktop";
+
+    fn desktop_entry_path() -> PathBuf {
+        let config_home = std::env::var("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| {
+                let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+                PathBuf::from(home).join(".config")
+            });
+        config_home.join("autostart").join(DESKTOP_FILE)
+    }
+
+    pub fn is_enabled() -> bool {
+        desktop_entry_path().exists()
+    }
+
+    pub fn set_enabled(enabled: bool) -> Result<(), String> {
+        let path = desktop_entry_path();
+
+        if enabled {
+            let exe_path = std::env::current_exe()
+                .map_err(|e| format!("Failed to get executable path: {}", e))?;
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)
+                    .map_err(|e| format!("Failed to create autostart directory: {}", e))?;
+            }
+            let entry = format!(
+                "[Desktop Entry]\nType=Application\nName=Shortcuts\nExec=\"{}\"\nX-GNOME-Autostart-enabled=true\n",
+                exe_path.to_string_lossy()
+            );
+            fs::write(&path, entry)
+                .map_err(|e| format!("Failed to write autostart entry: {}", e))?;
+        } else {
+            // Remove the entry (ignore error if it doesn't exist)
+            let _ = fs::remove_file(&path);
+        }
+
+        Ok(())
+    }
+}
+
+pub use imp::{is_enabled, set_enabled};
@@ -3,380 +3,293 @@
     windows_subsystem = "windows"
 )]
 
-mod config;
-
-use config::{ShortcutType, expand_env_vars, Shortcut, AppConfig, load_config, save_config, get_config_path};
+mod autostart;
+mod notify;
+mod watcher;
+
+use notify_debouncer_mini::{notify::RecommendedWatcher, Debouncer};
+use shortcut_core::sequence::{self, Step, StepRunner, Wait};
+use shortcut_core::template::{self, Param};
+use shortcut_core::{AppConfig, BackupInfo, ConfigError, ConfigStore, LaunchError, LogInfo, ProcessInfo, ProcessRegistry, Shortcut, ShortcutType, get_config_path, new_shortcut_id, set_config_path};
 use tauri::{
     tray::{TrayIconBuilder, TrayIconEvent, MouseButtonState, MouseButton},
     Manager, Emitter, PhysicalPosition,
 };
-use std::process::Command;
-use std::os::windows::process::CommandExt;
-use std::io::Write;
-use winreg::enums::*;
-use winreg::RegKey;
-use std::sync::Mutex;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
 
 struct TrayState {
     pub position: (i32, i32),
     pub size: (i32, i32),
 }
 
+/// Payload of the `launch-failed` event
+#[derive(Serialize, Clone)]
+struct LaunchFailed {
+    name: String,
+    error: LaunchError,
+}
+
 struct AppState {
     pub tray: Mutex<Option<TrayState>>,
+    // Single owner of the config; every command reads and writes through it
+    pub store: ConfigStore,
+    // Processes started from the popup, until they exit
+    pub processes: ProcessRegistry,
+    pub watcher: Mutex<Option<Debouncer<RecommendedWatcher>>>,
 }
 
-// Windows constants for CreateProcess
-const CREATE_NO_WINDOW: u32 = 0x08000000;
-const CREATE_NEW_CONSOLE: u32 = 0x00000010;
+/// Serves the in-memory config, which is the last good one while the file on disk
+/// is broken. The error is only returned if nothing has loaded yet; use
+/// `get_config_error` to tell.
+#[tauri::command]
+fn get_shortcuts(state: tauri::State<AppState>) -> Result<Vec<Shortcut>, ConfigError> {
+    Ok(state.store.snapshot()?.shortcuts)
+}
 
+/// Why config.json failed to load last time, if it did
 #[tauri::command]
-fn get_shortcuts() -> Vec<config::Shortcut> {
-    let cfg = load_config();
-    cfg.shortcuts
+fn get_config_error(state: tauri::State<AppState>) -> Option<ConfigError> {
+    state.store.error()
 }
 
+/// Re-read config.json from disk and tell both windows about the outcome
 #[tauri::command]
-fn add_shortcut(shortcut: Shortcut, app: tauri::AppHandle) -> Result<(), String> {
-    let mut cfg = load_config();
-    cfg.shortcuts.push(shortcut);
-    save_config(&cfg)?;
+fn reload_config(state: tauri::State<AppState>, app: tauri::AppHandle) {
+    match state.store.reload() {
+        Ok(_) => {
+            let _ = app.emit("reload-shortcuts", ());
+        }
+        Err(e) => {
+            let _ = app.emit("config-error", e);
+        }
+    }
+}
+
+#[tauri::command]
+fn add_shortcut(mut shortcut: Shortcut, state: tauri::State<AppState>, app: tauri::AppHandle) -> Result<(), String> {
+    state.store.update(|cfg| {
+        shortcut.id = new_shortcut_id();
+        cfg.shortcuts.push(shortcut);
+        Ok(())
+    })?;
     // Emit reload event to main window
     let _ = app.emit("reload-shortcuts", ());
     Ok(())
 }
 
 #[tauri::command]
-fn update_shortcut(index: usize, shortcut: Shortcut, app: tauri::AppHandle) -> Result<(), String> {
-    let mut cfg = load_config();
-    if index >= cfg.shortcuts.len() {
-        return Err("Index out of bounds".to_string());
-    }
-    cfg.shortcuts[index] = shortcut;
-    save_config(&cfg)?;
+fn update_shortcut(id: String, mut shortcut: Shortcut, state: tauri::State<AppState>, app: tauri::AppHandle) -> Result<(), String> {
+    state.store.update(|cfg| {
+        let index = cfg.position(&id).ok_or("Shortcut not found")?;
+        shortcut.id = id;
+        cfg.shortcuts[index] = shortcut;
+        Ok(())
+    })?;
     let _ = app.emit("reload-shortcuts", ());
     Ok(())
 }
 
 #[tauri::command]
-fn delete_shortcut(index: usize, app: tauri::AppHandle) -> Result<(), String> {
-    let mut cfg = load_config();
-    if index >= cfg.shortcuts.len() {
-        return Err("Index out of bounds".to_string());
-    }
-    cfg.shortcuts.remove(index);
-    save_config(&cfg)?;
-    let _ = app.emit("reload-shortcuts", ());
-    Ok(())
+fn shortcut_warnings(shortcut: Shortcut) -> Vec<String> {
+    shortcut_core::env::warnings(&shortcut)
 }
 
+/// Values the popup asks for before launching shortcut `id`
 #[tauri::command]
-fn reorder_shortcut(from_index: usize, to_index: usize, app: tauri::AppHandle) -> Result<(), String> {
-    let mut cfg = load_config();
-    if from_index >= cfg.shortcuts.len() || to_index >= cfg.shortcuts.len() {
-        return Err("Index out of bounds".to_string());
-    }
-    let item = cfg.shortcuts.remove(from_index);
-    cfg.shortcuts.insert(to_index, item);
-    save_config(&cfg)?;
+fn shortcut_params(id: String, state: tauri::State<AppState>) -> Result<Vec<Param>, LaunchError> {
+    let cfg = state.store.snapshot().map_err(|e| LaunchError::Invalid(e.to_string()))?;
+    let shortcut = cfg
+        .find(&id)
+        .ok_or_else(|| LaunchError::Invalid(format!("Shortcut not found: {}", id)))?;
+    template::params(shortcut, &cfg)
+}
+
+#[tauri::command]
+fn delete_shortcut(id: String, state: tauri::State<AppState>, app: tauri::AppHandle) -> Result<(), String> {
+    state.store.update(|cfg| {
+        let index = cfg.position(&id).ok_or("Shortcut not found")?;
+        cfg.shortcuts.remove(index);
+        Ok(())
+    })?;
     let _ = app.emit("reload-shortcuts", ());
     Ok(())
 }
 
+/// Move shortcut `id` in front of `before_id`, or to the end when `before_id` is None
 #[tauri::command]
-fn launch_shortcut(
-    shortcut_type: Option<String>,
-    command: String,
-    script: Option<String>,
-    args: Option<String>,
-    working_dir: Option<String>,
-    hidden: Option<bool>,
-    shell: Option<String>,
-    admin: Option<bool>,
-) {
-    let stype = shortcut_type
-        .as_deref()
-        .map(|s| match s.to_lowercase().as_str() {
-            "url" => ShortcutType::Url,
-            "file" => ShortcutType::File,
-            "folder" => ShortcutType::Folder,
-            "script" => ShortcutType::Script,
-            "shell" => ShortcutType::Shell,
-            _ => ShortcutType::App,
-        })
-        .unwrap_or(ShortcutType::App);
-
-    let expanded_command = expand_env_vars(&command);
-    let expanded_working_dir = working_dir.as_ref().map(|d| expand_env_vars(d));
-    let is_hidden = hidden.unwrap_or(false);
-    let is_admin = admin.unwrap_or(false);
+fn reorder_shortcut(id: String, before_id: Option<String>, state: tauri::State<AppState>, app: tauri::AppHandle) -> Result<(), String> {
+    state.store.update(|cfg| {
+        let from_index = cfg.position(&id).ok_or("Shortcut not found")?;
+        let item = cfg.shortcuts.remove(from_index);
+        let to_index = match before_id {
+            Some(before_id) => cfg.position(&before_id).ok_or("Target shortcut not found")?,
+            None => cfg.shortcuts.len(),
+        };
+        cfg.shortcuts.insert(to_index, item);
+        Ok(())
+    })?;
+    let _ = app.emit("reload-shortcuts", ());
+    Ok(())
+}
 
-    match stype {
-        ShortcutType::App => {
-            launch_app(&expanded_command, args.as_deref(), expanded_working_dir.as_deref(), is_admin);
-        }
-        ShortcutType::Url => {
-            open_url(&expanded_command);
-        }
-        ShortcutType::File => {
-            open_file(&expanded_command, is_admin);
-        }
-        ShortcutType::Folder => {
-            open_folder(&expanded_command);
-        }
-        ShortcutType::Script => {
-            run_script_file(&expanded_command, args.as_deref(), expanded_working_dir.as_deref(), is_hidden, is_admin);
-        }
-        ShortcutType::Shell => {
-            if let Some(script_content) = script {
-                run_shell_script(&script_content, shell.as_deref(), expanded_working_dir.as_deref(), is_hidden);
+/// Start the stored shortcut `id` with `values` for its placeholders and track its
+/// process; failures are broadcast as `launch-failed`. Without `values` the ones used
+/// last are taken again.
+fn launch_stored(id: &str, values: Option<BTreeMap<String, String>>, state: &AppState, app: &tauri::AppHandle) -> Result<(), LaunchError> {
+    let config = state.store.snapshot().map_err(|e| LaunchError::Invalid(e.to_string()));
+    let shortcut = config.as_ref().map_err(Clone::clone).and_then(|cfg| {
+        cfg.find(id)
+            .ok_or_else(|| LaunchError::Invalid(format!("Shortcut not found: {}", id)))
+    });
+    let name = shortcut.as_ref().map(|s| s.name.clone()).unwrap_or_default();
+    let values = values.unwrap_or_else(|| template::last_values(id));
+    shortcut
+        .and_then(|s| launch_tracked(s, &values, config.as_ref().map_err(Clone::clone)?, state, app))
+        .inspect(|_| {
+            if !values.is_empty() {
+                let _ = template::remember(id, &values);
             }
-        }
-    }
+        })
+        .inspect_err(|e| {
+            let _ = app.emit("launch-failed", LaunchFailed { name, error: e.clone() });
+        })
 }
 
-/// Launch an application
-fn launch_app(command: &str, args: Option<&str>, working_dir: Option<&str>, admin: bool) {
-    if admin {
-        // Use ShellExecute with runas for admin
-        let mut cmd = Command::new("powershell");
-        cmd.args(["-Command", &format!("Start-Process '{}' -Verb RunAs", command)]);
-        cmd.creation_flags(CREATE_NO_WINDOW);
-        let _ = cmd.spawn();
-    } else {
-        let mut cmd = Command::new(command);
-        if let Some(arg_str) = args {
-            // Better argument parsing - handle quoted strings
-            for arg in parse_args(arg_str) {
-                cmd.arg(arg);
-            }
-        }
-        if let Some(dir) = working_dir {
-            cmd.current_dir(dir);
-        }
-        let _ = cmd.spawn();
+/// Launch `shortcut` from `config` with its placeholders filled in from `values`; the
+/// steps of a sequence run on a thread of their own
+fn launch_tracked(shortcut: &Shortcut, values: &BTreeMap<String, String>, config: &AppConfig, state: &AppState, app: &tauri::AppHandle) -> Result<(), LaunchError> {
+    if shortcut.shortcut_type != ShortcutType::Sequence {
+        let shortcut = template::fill(shortcut, values)?;
+        return state.processes.launch(&shortcut, config.terminal.as_deref()).map(|_| ());
     }
+    let steps = sequence::fill(&sequence::resolve(shortcut, config)?, values)?;
+    let runner = Arc::new(TrayRunner {
+        processes: state.processes.clone(),
+        app: app.clone(),
+        terminal: config.terminal.clone(),
+    });
+    std::thread::spawn(move || sequence::run(&steps, &runner));
+    Ok(())
 }
 
-/// Open URL in default browser
-fn open_url(url: &str) {
-    let _ = Command::new("cmd")
-        .args(["/C", "start", "", url])
-        .creation_flags(CREATE_NO_WINDOW)
-        .spawn();
+/// Starts the steps of sequences, tracking each like a launch from the popup
+struct TrayRunner {
+    processes: ProcessRegistry,
+    app: tauri::AppHandle,
+    terminal: Option<String>,
 }
 
-/// Open file with default application
-fn open_file(path: &str, admin: bool) {
-    if admin {
-        let mut cmd = Command::new("powershell");
-        cmd.args(["-Command", &format!("Start-Process '{}' -Verb RunAs", path)]);
-        cmd.creation_flags(CREATE_NO_WINDOW);
-        let _ = cmd.spawn();
-    } else {
-        let _ = Command::new("cmd")
-            .args(["/C", "start", "", path])
-            .creation_flags(CREATE_NO_WINDOW)
-            .spawn();
+impl StepRunner for TrayRunner {
+    fn start(&self, step: &Step) -> Result<Option<Wait>, LaunchError> {
+        let process = self.processes.launch_step(step, self.terminal.as_deref())?;
+        let processes = self.processes.clone();
+        Ok(process.map(|process| Box::new(move || processes.wait(&process.run_id)) as Wait))
     }
-}
 
-/// Open folder in explorer
-fn open_folder(path: &str) {
-    let _ = Command::new("explorer")
-        .arg(path)
-        .spawn();
-}
-
-/// Run a script file (.bat, .ps1, .cmd, etc.)
-fn run_script_file(path: &str, args: Option<&str>, working_dir: Option<&str>, hidden: bool, admin: bool) {
-    let extension = std::path::Path::new(path)
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("")
-        .to_lowercase();
-
-    match extension.as_str() {
-        "ps1" => {
-            // PowerShell script
-            let mut cmd = Command::new("powershell");
-            cmd.args(["-ExecutionPolicy", "Bypass", "-File", path]);
-            if let Some(arg_str) = args {
-                for arg in parse_args(arg_str) {
-                    cmd.arg(arg);
-                }
-            }
-            if let Some(dir) = working_dir {
-                cmd.current_dir(dir);
-            }
-            if hidden {
-                cmd.creation_flags(CREATE_NO_WINDOW);
-            }
-            if admin {
-                // Re-launch with elevation
-                let script_args = args.unwrap_or("");
-                let ps_cmd = format!(
-                    "Start-Process powershell -Verb RunAs -ArgumentList '-ExecutionPolicy Bypass -File \"{}\" {}'",
-                    path, script_args
-                );
-                let mut admin_cmd = Command::new("powershell");
-                admin_cmd.args(["-Command", &ps_cmd]);
-                admin_cmd.creation_flags(CREATE_NO_WINDOW);
-                let _ = admin_cmd.spawn();
-            } else {
-                let _ = cmd.spawn();
-            }
-        }
-        "bat" | "cmd" => {
-            // Batch script
-            let mut cmd = Command::new("cmd");
-            cmd.args(["/C", path]);
-            if let Some(arg_str) = args {
-                for arg in parse_args(arg_str) {
-                    cmd.arg(arg);
-                }
-            }
-            if let Some(dir) = working_dir {
-                cmd.current_dir(dir);
-            }
-            if hidden {
-                cmd.creation_flags(CREATE_NO_WINDOW);
-            } else {
-                cmd.creation_flags(CREATE_NEW_CONSOLE);
-            }
-            if admin {
-                let script_args = args.unwrap_or("");
-                let ps_cmd = format!(
-                    "Start-Process cmd -Verb RunAs -ArgumentList '/C \"{}\" {}'",
-                    path, script_args
-                );
-                let mut admin_cmd = Command::new("powershell");
-                admin_cmd.args(["-Command", &ps_cmd]);
-                admin_cmd.creation_flags(CREATE_NO_WINDOW);
-                let _ = admin_cmd.spawn();
-            } else {
-                let _ = cmd.spawn();
-            }
-        }
-        _ => {
-            // Unknown script type - try to run directly
-            let mut cmd = Command::new(path);
-            if let Some(arg_str) = args {
-                for arg in parse_args(arg_str) {
-                    cmd.arg(arg);
-                }
-            }
-            if let Some(dir) = working_dir {
-                cmd.current_dir(dir);
-            }
-            let _ = cmd.spawn();
-        }
+    fn failed(&self, shortcut: &Shortcut, error: &LaunchError) {
+        let _ = self.app.emit("launch-failed", LaunchFailed { name: shortcut.name.clone(), error: error.clone() });
     }
 }
 
-/// Run inline shell script content
-fn run_shell_script(script_content: &str, shell: Option<&str>, working_dir: Option<&str>, hidden: bool) {
-    let shell_type = shell.unwrap_or("cmd").to_lowercase();
+/// Start the stored shortcut `id`, filling its placeholders from `values`.
+///
+/// Only shortcuts from the config can be launched, so the webview cannot run
+/// arbitrary commands.
+#[tauri::command]
+fn launch_shortcut_by_id(id: String, values: Option<BTreeMap<String, String>>, state: tauri::State<AppState>, app: tauri::AppHandle) -> Result<(), LaunchError> {
+    launch_stored(&id, values, &state, &app)
+}
 
-    match shell_type.as_str() {
-        "powershell" | "pwsh" => {
-            // Create temp ps1 file
-            let temp_dir = std::env::temp_dir();
-            let temp_file = temp_dir.join(format!("shortcut_script_{}.ps1", std::process::id()));
+/// Processes launched from the popup, newest first
+#[tauri::command]
+fn list_processes(state: tauri::State<AppState>) -> Vec<ProcessInfo> {
+    state.processes.list()
+}
 
-            if let Ok(mut file) = std::fs::File::create(&temp_file) {
-                let _ = file.write_all(script_content.as_bytes());
+/// Stop a running process and everything it started
+#[tauri::command]
+fn cancel_process(run_id: String, state: tauri::State<AppState>) -> Result<(), String> {
+    state.processes.cancel(&run_id)
+}
 
-                let shell_exe = if shell_type == "pwsh" { "pwsh" } else { "powershell" };
-                let mut cmd = Command::new(shell_exe);
-                cmd.args(["-ExecutionPolicy", "Bypass", "-File", temp_file.to_str().unwrap()]);
+/// Stop the process if it is still running and launch its shortcut again
+#[tauri::command]
+fn restart_process(run_id: String, state: tauri::State<AppState>, app: tauri::AppHandle) -> Result<(), LaunchError> {
+    let process = state
+        .processes
+        .get(&run_id)
+        .ok_or_else(|| LaunchError::Invalid(format!("No process with run ID {}", run_id)))?;
+    if process.is_running() {
+        state.processes.cancel(&run_id).map_err(LaunchError::Failed)?;
+    }
+    // An inline sequence step is not in the config; run it again as it ran before
+    if let Some(step) = &process.step {
+        let terminal = state.store.snapshot().ok().and_then(|cfg| cfg.terminal);
+        return state.processes.launch(step, terminal.as_deref()).map(|_| ()).inspect_err(|e| {
+            let _ = app.emit("launch-failed", LaunchFailed { name: step.name.clone(), error: e.clone() });
+        });
+    }
+    launch_stored(&process.shortcut_id, None, &state, &app)
+}
 
-                if let Some(dir) = working_dir {
-                    cmd.current_dir(dir);
-                }
-                if hidden {
-                    cmd.creation_flags(CREATE_NO_WINDOW);
-                }
-                let _ = cmd.spawn();
-            }
-        }
-        "cmd" | _ => {
-            // Create temp bat file
-            let temp_dir = std::env::temp_dir();
-            let temp_file = temp_dir.join(format!("shortcut_script_{}.bat", std::process::id()));
+#[tauri::command]
+fn clear_finished_processes(state: tauri::State<AppState>) {
+    state.processes.clear_finished();
+}
 
-            if let Ok(mut file) = std::fs::File::create(&temp_file) {
-                // Add @echo off and cleanup command
-                let batch_content = format!("@echo off\r\n{}\r\n", script_content.replace('\n', "\r\n"));
-                let _ = file.write_all(batch_content.as_bytes());
+/// Captured output of hidden runs, newest first
+#[tauri::command]
+fn list_logs() -> Vec<LogInfo> {
+    shortcut_core::list_logs()
+}
 
-                let mut cmd = Command::new("cmd");
-                cmd.args(["/C", temp_file.to_str().unwrap()]);
+#[tauri::command]
+fn read_log(run_id: String) -> Result<String, String> {
+    shortcut_core::read_log(&run_id)
+}
 
-                if let Some(dir) = working_dir {
-                    cmd.current_dir(dir);
-                }
-                if hidden {
-                    cmd.creation_flags(CREATE_NO_WINDOW);
-                } else {
-                    cmd.creation_flags(CREATE_NEW_CONSOLE);
-                }
-                let _ = cmd.spawn();
-            }
-        }
-    }
+#[tauri::command]
+fn export_config(path: String, state: tauri::State<AppState>) -> Result<(), String> {
+    state.store.export(Path::new(&path))
 }
 
-/// Parse arguments respecting quoted strings
-fn parse_args(arg_str: &str) -> Vec<String> {
-    let mut args = Vec::new();
-    let mut current = String::new();
-    let mut in_quotes = false;
-    let mut quote_char = ' ';
+#[tauri::command]
+fn import_config(path: String, state: tauri::State<AppState>, app: tauri::AppHandle) -> Result<(), String> {
+    state.store.import(Path::new(&path))?;
+    let _ = app.emit("reload-shortcuts", ());
+    Ok(())
+}
 
-    for c in arg_str.chars() {
-        match c {
-            '"' | '\'' if !in_quotes => {
-                in_quotes = true;
-                quote_char = c;
-            }
-            c if c == quote_char && in_quotes => {
-                in_quotes = false;
-            }
-            ' ' if !in_quotes => {
-                if !current.is_empty() {
-                    args.push(current.clone());
-                    current.clear();
-                }
-            }
-            _ => {
-                current.push(c);
-            }
-        }
-    }
+#[tauri::command]
+fn get_config_location() -> String {
+    get_config_path().display().to_string()
+}
 
-    if !current.is_empty() {
-        args.push(current);
-    }
+/// Terminal visible scripts open in on Linux; None when one is detected
+#[tauri::command]
+fn get_terminal(state: tauri::State<AppState>) -> Result<Option<String>, String> {
+    Ok(state.store.snapshot()?.terminal)
+}
 
-    args
+#[tauri::command]
+fn set_terminal(terminal: Option<String>, state: tauri::State<AppState>) -> Result<(), String> {
+    state.store.update(|cfg| {
+        cfg.terminal = terminal.filter(|t| !t.trim().is_empty());
+        Ok(())
+    })
 }
 
 #[tauri::command]
-fn export_config(path: String) -> Result<(), String> {
-    let src = get_config_path();
-    std::fs::copy(&src, &path)
-        .map_err(|e| format!("Failed to export config: {}", e))?;
-    Ok(())
+fn list_backups() -> Result<Vec<BackupInfo>, String> {
+    shortcut_core::list_backups()
 }
 
 #[tauri::command]
-fn import_config(path: String, app: tauri::AppHandle) -> Result<(), String> {
-    let content = std::fs::read_to_string(&path)
-        .map_err(|e| format!("Failed to read file: {}", e))?;
-    let config: AppConfig = serde_json::from_str(&content)
-        .map_err(|e| format!("Invalid config file: {}", e))?;
-    save_config(&config)?;
+fn restore_backup(name: String, state: tauri::State<AppState>, app: tauri::AppHandle) -> Result<(), String> {
+    state.store.restore_backup(&name)?;
     let _ = app.emit("reload-shortcuts", ());
     Ok(())
 }
@@ -431,46 +344,56 @@ fn resize_main_window(width: f64, height: f64, window: tauri::Window, state: tau
     }
 }
 
-const AUTOSTART_KEY: &str = "Shortcuts";
-const RUN_KEY_PATH: &str = r"Software\Microsoft\Windows\CurrentVersion\Run";
-
 #[tauri::command]
 fn get_autostart() -> bool {
-    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-    if let Ok(run_key) = hkcu.open_subkey(RUN_KEY_PATH) {
-        run_key.get_value::<String, _>(AUTOSTART_KEY).is_ok()
-    } else {
-        false
-    }
+    autostart::is_enabled()
 }
 
 #[tauri::command]
 fn set_autostart(enabled: bool) -> Result<(), String> {
-    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-    let run_key = hkcu.open_subkey_with_flags(RUN_KEY_PATH, KEY_WRITE)
-        .map_err(|e| format!("Failed to open registry key: {}", e))?;
-
-    if enabled {
-        // Get the current executable path
-        let exe_path = std::env::current_exe()
-            .map_err(|e| format!("Failed to get executable path: {}", e))?;
-        let exe_str = exe_path.to_string_lossy().to_string();
-
-        run_key.set_value(AUTOSTART_KEY, &exe_str)
-            .map_err(|e| format!("Failed to set registry value: {}", e))?;
-    } else {
-        // Remove the registry value (ignore error if it doesn't exist)
-        let _ = run_key.delete_value(AUTOSTART_KEY);
-    }
+    autostart::set_enabled(enabled)
+}
 
-    Ok(())
+/// Honour `--config <path>` / `--config=<path>` before anything reads the config
+fn apply_config_arg() {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            if let Some(path) = args.next() {
+                set_config_path(path);
+            }
+        } else if let Some(path) = arg.strip_prefix("--config=") {
+            set_config_path(path);
+        }
+    }
 }
 
 fn main() {
+    apply_config_arg();
+
     tauri::Builder::default()
-        .manage(AppState { tray: Mutex::new(None) })
+        .manage(AppState {
+            tray: Mutex::new(None),
+            store: ConfigStore::open(),
+            processes: ProcessRegistry::open(),
+            watcher: Mutex::new(None),
+        })
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
         .setup(|app| {
+            shortcut_core::sweep_temp_scripts();
+            let state = app.state::<AppState>();
+            let handle = app.handle().clone();
+            state.processes.on_change(move || {
+                let _ = handle.emit("processes-changed", ());
+            });
+            let handle = app.handle().clone();
+            state.processes.on_exit(move |process| notify::process_exited(&handle, process));
+            match watcher::watch_config(app.handle().clone()) {
+                Ok(watcher) => *state.watcher.lock().unwrap() = Some(watcher),
+                Err(e) => println!("{}", e),
+            }
+
             // Setup Tray (left-click only, no right-click menu)
             let _tray = TrayIconBuilder::new()
                 .icon(app.default_window_icon().unwrap().clone())
@@ -528,7 +451,7 @@ fn main() {
                 }
             }
         })
-        .invoke_handler(tauri::generate_handler![get_shortcuts, add_shortcut, update_shortcut, delete_shortcut, reorder_shortcut, launch_shortcut, hide_window, open_settings, exit_app, get_autostart, set_autostart, resize_main_window, export_config, import_config])
+        .invoke_handler(tauri::generate_handler![get_shortcuts, get_config_error, reload_config, add_shortcut, update_shortcut, shortcut_warnings, delete_shortcut, reorder_shortcut, shortcut_params, launch_shortcut_by_id, list_processes, cancel_process, restart_process, clear_finished_processes, list_logs, read_log, hide_window, open_settings, exit_app, get_autostart, set_autostart, resize_main_window, export_config, import_config, get_config_location, get_terminal, set_terminal, list_backups, restore_backup])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -0,0 +1,47 @@
+//! Desktop notifications when a run of a `notify_on_exit` shortcut ends.
+
+use shortcut_core::logs::log_tail;
+use shortcut_core::{ProcessInfo, ProcessStatus};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::AppState;
+
+// Lines of output quoted in the notification
+const TAIL_LINES: usize = 3;
+
+/// Notify about `process` if the shortcut that started it asks for it
+pub fn process_exited(app: &AppHandle, process: &ProcessInfo) {
+    let state = app.state::<AppState>();
+    let wanted = match &process.step {
+        Some(step) => step.notify_on_exit,
+        None => state
+            .store
+            .snapshot()
+            .ok()
+            .and_then(|cfg| cfg.find(&process.shortcut_id).map(|s| s.notify_on_exit))
+            .unwrap_or(false),
+    };
+    if !wanted {
+        return;
+    }
+
+    let mut body = match process.status {
+        ProcessStatus::Exited { code: Some(0) } => "Finished successfully".to_string(),
+        ProcessStatus::Exited { code: Some(code) } => format!("Exited with code {}", code),
+        ProcessStatus::Exited { code: None } => "Exited".to_string(),
+        ProcessStatus::Killed => "Cancelled".to_string(),
+        ProcessStatus::TimedOut => "Timed out".to_string(),
+        ProcessStatus::Running => return,
+    };
+    if process.has_log {
+        if let Some(tail) = log_tail(&process.run_id, TAIL_LINES) {
+            body.push('\n');
+            body.push_str(&tail);
+        }
+    }
+
+    if let Err(e) = app.notification().builder().title(&process.shortcut_name).body(body).show() {
+        println!("Failed to show notification: {}", e);
+    }
+}
@@ -0,0 +1,52 @@
+//! Picks up edits made to config.json by editors and dotfile sync tools.
+
+use notify_debouncer_mini::notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use shortcut_core::get_config_path;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::AppState;
+
+// Editors often write a file in several steps; wait for them to settle
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Watch the config file and emit `reload-shortcuts`, or `config-error` when the new content is invalid.
+///
+/// The directory is watched rather than the file itself, because atomic saves
+/// (ours included) replace config.json with a new file.
+pub fn watch_config(app: AppHandle) -> Result<Debouncer<RecommendedWatcher>, String> {
+    let config_path = get_config_path();
+    let file_name = config_path.file_name().map(|n| n.to_os_string());
+    let dir = config_path
+        .parent()
+        .ok_or("Config path has no parent directory")?
+        .to_path_buf();
+
+    let mut debouncer = new_debouncer(DEBOUNCE, move |res: DebounceEventResult| {
+        let Ok(events) = res else {
+            return;
+        };
+        if !events.iter().any(|e| e.path.file_name() == file_name.as_deref()) {
+            return;
+        }
+
+        let state = app.state::<AppState>();
+        match state.store.reload() {
+            Ok(_) => {
+                let _ = app.emit("reload-shortcuts", ());
+            }
+            // The last good config stays in memory, so the popup keeps working
+            Err(e) => {
+                let _ = app.emit("config-error", e);
+            }
+        }
+    })
+    .map_err(|e| format!("Failed to create config watcher: {}", e))?;
+
+    debouncer
+        .watcher()
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+    Ok(debouncer)
+}
@@ -33,12 +33,27 @@ const icons = {
     // Shell / Terminal
     shell: `<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><polyline points="4 17 10 11 4 5"></polyline><line x1="12" y1="19" x2="20" y2="19"></line></svg>`,
 
+    // Sequence of shortcuts
+    sequence: `<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><polygon points="12 2 2 7 12 12 22 7 12 2"></polygon><polyline points="2 17 12 22 22 17"></polyline><polyline points="2 12 12 17 22 12"></polyline></svg>`,
+
     // Arrow indicator
     arrow: `<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><polyline points="9 18 15 12 9 6"></polyline></svg>`,
 
     // Empty state
     empty: `<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><rect x="3" y="3" width="18" height="18" rx="2" ry="2"></rect><line x1="9" y1="9" x2="15" y2="15"></line><line x1="15" y1="9" x2="9" y2="15"></line></svg>`,
 
+    // Process activity
+    activity: `<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><polyline points="22 12 18 12 15 21 9 3 6 12 2 12"></polyline></svg>`,
+
+    // Stop process
+    stop: `<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><rect x="5" y="5" width="14" height="14" rx="2" ry="2"></rect></svg>`,
+
+    // Log output
+    log: `<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><line x1="8" y1="6" x2="21" y2="6"></line><line x1="8" y1="12" x2="21" y2="12"></line><line x1="8" y1="18" x2="21" y2="18"></line><line x1="3" y1="6" x2="3.01" y2="6"></line><line x1="3" y1="12" x2="3.01" y2="12"></line><line x1="3" y1="18" x2="3.01" y2="18"></line></svg>`,
+
+    // Restart process
+    restart: `<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><polyline points="1 4 1 10 7 10"></polyline><path d="M3.51 15a9 9 0 1 0 2.13-9.36L1 10"></path></svg>`,
+
     // Admin shield
     admin: `<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><path d="M12 22s8-4 8-10V5l-8-3-8 3v7c0 6 8 10 8 10z"></path></svg>`
 };
@@ -51,6 +66,14 @@ const iconColors = {
     folder: { from: '#eab308', to: '#facc15' },   // Yellow
     script: { from: '#22c55e', to: '#10b981' },   // Green to emerald
     shell: { from: '#a855f7', to: '#d946ef' },    // Purple to fuchsia
+    sequence: { from: '#f43f5e', to: '#ec4899' }, // Rose to pink
+};
+
+// Icon colors for process states
+const statusColors = {
+    running: { from: '#22c55e', to: '#10b981' },  // Green to emerald
+    ok: { from: '#64748b', to: '#94a3b8' },       // Slate
+    failed: { from: '#ef4444', to: '#f97316' },   // Red to orange
 };
 
 // Get icon based on shortcut type
@@ -77,14 +100,31 @@ whenReady(() => {
     const { invoke } = window.__TAURI__.core;
     const { listen } = window.__TAURI__.event;
 
+    // Which list the popup shows: 'shortcuts', 'processes', 'log' or 'params'
+    let view = 'shortcuts';
+
+    function setView(next) {
+        view = next;
+        document.getElementById('btn-processes').classList.toggle('active', view !== 'shortcuts');
+        const titles = { shortcuts: 'Shortcuts', processes: 'Processes', log: 'Log', params: 'Launch' };
+        document.querySelector('.header-title').textContent = titles[view];
+    }
+
     // Header button handlers
+    document.getElementById('btn-processes').onclick = () => {
+        // From the log view this goes back to the process list
+        setView(view === 'processes' ? 'shortcuts' : 'processes');
+        refresh();
+    };
+
     document.getElementById('btn-settings').onclick = () => {
         invoke('open_settings');
         invoke('hide_window');
     };
 
+    // Re-read config.json from disk; the backend answers with reload-shortcuts
     document.getElementById('btn-reload').onclick = () => {
-        loadShortcuts();
+        invoke('reload_config');
     };
 
     document.getElementById('btn-exit').onclick = () => {
@@ -154,7 +194,11 @@ whenReady(() => {
                 if (item.description) {
                     descSpan.textContent = item.description;
                 } else if (shortcutType === 'shell' && item.shell) {
-                    descSpan.textContent = `${item.shell} script`;
+                    const shell = item.shell === 'interpreter' && item.interpreter ? item.interpreter : item.shell;
+                    descSpan.textContent = `${shell} script`;
+                } else if (shortcutType === 'sequence') {
+                    const count = (item.steps || []).length;
+                    descSpan.textContent = `${count} step${count === 1 ? '' : 's'}`;
                 } else if (shortcutType === 'url') {
                     // Show domain for URLs
                     try {
@@ -186,17 +230,18 @@ whenReady(() => {
                         li.style.transform = '';
                     }, 100);
 
-                    await invoke('launch_shortcut', {
-                        shortcutType: shortcutType,
-                        command: item.command || '',
-                        script: item.script || null,
-                        args: item.args || null,
-                        workingDir: item.working_dir || null,
-                        hidden: item.hidden || false,
-                        shell: item.shell || null,
-                        admin: item.admin || false
-                    });
-                    await invoke('hide_window');
+                    let params;
+                    try {
+                        params = await invoke('shortcut_params', { id: item.id });
+                    } catch (e) {
+                        showToast(`${item.name}: ${e.message}`);
+                        return;
+                    }
+                    if (params.length > 0) {
+                        showParams(item, params);
+                    } else {
+                        launch(item.id, {});
+                    }
                 };
 
                 li.onclick = launchShortcut;
@@ -220,8 +265,241 @@ whenReady(() => {
                 <div class="empty-state">
                     ${icons.empty}
                     <p>Failed to load shortcuts.<br>Check config file format.</p>
+                    <p class="error-detail"></p>
                 </div>
             `;
+            // e is a ConfigError with the position of the problem
+            const where = e.line ? `Line ${e.line}, column ${e.column}: ` : '';
+            list.querySelector('.error-detail').textContent = where + (e.message || e);
+            updateWindowSize(0);
+        }
+    }
+
+    async function launch(id, values) {
+        try {
+            await invoke('launch_shortcut_by_id', { id, values });
+            await invoke('hide_window');
+        } catch (e) {
+            // Stay open; the launch-failed event shows the reason
+            console.error('Failed to launch shortcut', e);
+        }
+    }
+
+    // Ask for the placeholder values of `item`, prefilled with the last ones used
+    function showParams(item, params) {
+        setView('params');
+        const list = document.getElementById('shortcut-list');
+        list.innerHTML = '';
+
+        const li = document.createElement('li');
+        li.className = 'params-view';
+        const form = document.createElement('form');
+        form.className = 'params-form';
+        const title = document.createElement('div');
+        title.className = 'params-title';
+        title.textContent = item.name;
+        form.appendChild(title);
+
+        const inputs = params.map(param => {
+            const label = document.createElement('label');
+            label.className = 'params-field';
+            const name = document.createElement('span');
+            name.textContent = param.name;
+            label.appendChild(name);
+
+            const value = param.last ?? param.default ?? '';
+            let input;
+            if (param.choices.length > 0) {
+                input = document.createElement('select');
+                param.choices.forEach(choice => input.add(new Option(choice, choice)));
+                input.value = param.choices.includes(value) ? value : param.choices[0];
+            } else {
+                input = document.createElement('input');
+                input.type = 'text';
+                input.value = value;
+                if (param.default !== null) input.placeholder = param.default;
+            }
+            label.appendChild(input);
+            form.appendChild(label);
+            return [param.name, input];
+        });
+
+        const actions = document.createElement('div');
+        actions.className = 'params-actions';
+        const cancel = document.createElement('button');
+        cancel.type = 'button';
+        cancel.textContent = 'Cancel';
+        cancel.onclick = () => {
+            setView('shortcuts');
+            loadShortcuts();
+        };
+        const run = document.createElement('button');
+        run.type = 'submit';
+        run.className = 'params-run';
+        run.textContent = 'Launch';
+        actions.appendChild(cancel);
+        actions.appendChild(run);
+        form.appendChild(actions);
+
+        form.onsubmit = (e) => {
+            e.preventDefault();
+            const values = {};
+            inputs.forEach(([name, input]) => {
+                // Left out when empty so the default applies
+                if (input.value !== '') values[name] = input.value;
+            });
+            launch(item.id, values);
+        };
+
+        li.appendChild(form);
+        list.appendChild(li);
+        document.documentElement.style.setProperty('--grid-cols', 1);
+        invoke('resize_main_window', { width: 320, height: HEADER_HEIGHT + 96 + params.length * 58 });
+        inputs[0][1].focus();
+    }
+
+    function describeProcess(proc) {
+        switch (proc.status.state) {
+            case 'running':
+                return `PID ${proc.pid} · running since ${proc.started}`;
+            case 'exited':
+                return proc.status.code === null
+                    ? `Exited · ${proc.ended}`
+                    : `Exit code ${proc.status.code} · ${proc.ended}`;
+            case 'timed_out':
+                return proc.ended ? `Timed out · ${proc.ended}` : 'Timed out';
+            default:
+                return proc.ended ? `Cancelled · ${proc.ended}` : 'Cancelled';
+        }
+    }
+
+    function processColors(proc) {
+        if (proc.status.state === 'running') return statusColors.running;
+        if (proc.status.state === 'exited' && proc.status.code === 0) return statusColors.ok;
+        return statusColors.failed;
+    }
+
+    function processButton(className, title, icon, onClick) {
+        const btn = document.createElement('button');
+        btn.className = `process-btn ${className}`;
+        btn.title = title;
+        btn.innerHTML = icon;
+        btn.onclick = (e) => {
+            e.stopPropagation();
+            onClick();
+        };
+        return btn;
+    }
+
+    async function loadProcesses() {
+        const list = document.getElementById('shortcut-list');
+        const processes = await invoke('list_processes');
+        list.innerHTML = '';
+
+        if (processes.length === 0) {
+            const emptyDiv = document.createElement('div');
+            emptyDiv.className = 'empty-state';
+            emptyDiv.innerHTML = `
+                ${icons.activity}
+                <p>Nothing launched yet.</p>
+            `;
+            list.appendChild(emptyDiv);
+            updateWindowSize(0);
+            return;
+        }
+
+        processes.forEach((proc, index) => {
+            const li = document.createElement('li');
+            li.className = 'shortcut-item process-item';
+            li.style.animationDelay = `${index * 0.02}s`;
+
+            const iconDiv = document.createElement('div');
+            iconDiv.className = 'shortcut-icon';
+            const colors = processColors(proc);
+            iconDiv.style.background = `linear-gradient(135deg, ${colors.from}, ${colors.to})`;
+            iconDiv.innerHTML = icons.activity;
+
+            const contentDiv = document.createElement('div');
+            contentDiv.className = 'shortcut-content';
+
+            const nameSpan = document.createElement('span');
+            nameSpan.className = 'shortcut-name';
+            nameSpan.textContent = proc.shortcut_name;
+            contentDiv.appendChild(nameSpan);
+
+            const descSpan = document.createElement('span');
+            descSpan.className = 'shortcut-desc';
+            descSpan.textContent = describeProcess(proc);
+            contentDiv.appendChild(descSpan);
+
+            const actions = document.createElement('div');
+            actions.className = 'process-actions';
+            if (proc.has_log) {
+                actions.appendChild(processButton('process-btn-log', 'Show output', icons.log, () => showLog(proc)));
+            }
+            actions.appendChild(processButton('process-btn-restart', 'Restart', icons.restart, () => {
+                // Launch failures arrive as launch-failed
+                invoke('restart_process', { runId: proc.run_id }).catch(e => console.error('Failed to restart', e));
+            }));
+            if (proc.status.state === 'running') {
+                actions.appendChild(processButton('process-btn-kill', 'Cancel', icons.stop, () => {
+                    invoke('cancel_process', { runId: proc.run_id }).catch(e => showToast(e));
+                }));
+            }
+
+            li.appendChild(iconDiv);
+            li.appendChild(contentDiv);
+            li.appendChild(actions);
+            list.appendChild(li);
+        });
+
+        let rows = processes.length;
+        if (processes.some(p => p.status.state !== 'running')) {
+            const clearLi = document.createElement('li');
+            clearLi.className = 'process-clear';
+            const clearBtn = document.createElement('button');
+            clearBtn.textContent = 'Clear finished';
+            clearBtn.onclick = () => invoke('clear_finished_processes');
+            clearLi.appendChild(clearBtn);
+            list.appendChild(clearLi);
+            rows += 1;
+        }
+
+        updateWindowSize(rows);
+    }
+
+    async function showLog(proc) {
+        setView('log');
+        const list = document.getElementById('shortcut-list');
+        list.innerHTML = '';
+
+        const li = document.createElement('li');
+        li.className = 'log-view';
+        const title = document.createElement('div');
+        title.className = 'log-title';
+        title.textContent = `${proc.shortcut_name} · ${describeProcess(proc)}`;
+        const pre = document.createElement('pre');
+        pre.className = 'log-output';
+        li.appendChild(title);
+        li.appendChild(pre);
+        list.appendChild(li);
+
+        try {
+            const output = await invoke('read_log', { runId: proc.run_id });
+            pre.textContent = output || '(no output)';
+        } catch (e) {
+            pre.textContent = e;
+        }
+        document.documentElement.style.setProperty('--grid-cols', 1);
+        invoke('resize_main_window', { width: 520, height: 400 });
+        pre.scrollTop = pre.scrollHeight;
+    }
+
+    function refresh() {
+        if (view === 'processes') {
+            loadProcesses();
+        } else {
+            loadShortcuts();
         }
     }
 
@@ -246,6 +524,16 @@ whenReady(() => {
         invoke('resize_main_window', { width: Math.round(width), height: Math.round(height) });
     }
 
+    let toastTimer = null;
+
+    function showToast(message) {
+        const toast = document.getElementById('toast');
+        toast.textContent = message;
+        toast.classList.add('visible');
+        clearTimeout(toastTimer);
+        toastTimer = setTimeout(() => toast.classList.remove('visible'), 4000);
+    }
+
     // Hide on blur
     window.addEventListener('blur', () => {
         invoke('hide_window');
@@ -276,7 +564,18 @@ whenReady(() => {
 
     // Listen for reload event from backend
     listen('reload-shortcuts', () => {
-        loadShortcuts();
+        if (view === 'shortcuts') loadShortcuts();
+    });
+
+    // A tracked process started, ended or was cleared
+    listen('processes-changed', () => {
+        if (view === 'processes') loadProcesses();
+    });
+
+    // A launch failed, from this popup or elsewhere
+    listen('launch-failed', (event) => {
+        const { name, error } = event.payload;
+        showToast(name ? `${name}: ${error.message}` : error.message);
     });
 
     // Initial load
@@ -21,6 +21,7 @@ const icons = {
     folder: `<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><path d="M22 19a2 2 0 0 1-2 2H4a2 2 0 0 1-2-2V5a2 2 0 0 1 2-2h5l2 3h9a2 2 0 0 1 2 2z"></path></svg>`,
     script: `<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><path d="M14 2H6a2 2 0 0 0-2 2v16a2 2 0 0 0 2 2h12a2 2 0 0 0 2-2V8z"></path><polyline points="14 2 14 8 20 8"></polyline><line x1="16" y1="13" x2="8" y2="13"></line><line x1="16" y1="17" x2="8" y2="17"></line><polyline points="10 9 9 9 8 9"></polyline></svg>`,
     shell: `<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><polyline points="4 17 10 11 4 5"></polyline><line x1="12" y1="19" x2="20" y2="19"></line></svg>`,
+    sequence: `<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><polygon points="12 2 2 7 12 12 22 7 12 2"></polygon><polyline points="2 17 12 22 22 17"></polyline><polyline points="2 12 12 17 22 12"></polyline></svg>`,
     empty: `<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><rect x="3" y="3" width="18" height="18" rx="2" ry="2"></rect><line x1="9" y1="9" x2="15" y2="15"></line><line x1="15" y1="9" x2="9" y2="15"></line></svg>`,
     drag: `<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><circle cx="9" cy="5" r="1"></circle><circle cx="9" cy="12" r="1"></circle><circle cx="9" cy="19" r="1"></circle><circle cx="15" cy="5" r="1"></circle><circle cx="15" cy="12" r="1"></circle><circle cx="15" cy="19" r="1"></circle></svg>`,
     edit: `<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><path d="M11 4H4a2 2 0 0 0-2 2v14a2 2 0 0 0 2 2h14a2 2 0 0 0 2-2v-7"></path><path d="M18.5 2.5a2.121 2.121 0 0 1 3 3L12 15l-4 1 1-4 9.5-9.5z"></path></svg>`,
@@ -35,10 +36,12 @@ const typeLabels = {
     folder: 'Folder',
     script: 'Script',
     shell: 'Shell',
+    sequence: 'Sequence',
 };
 
 whenReady(() => {
     const { invoke } = window.__TAURI__.core;
+    const { listen } = window.__TAURI__.event;
 
     // Tab switching
     const tabs = document.querySelectorAll('.tab');
@@ -81,6 +84,27 @@ whenReady(() => {
     // Load autostart state on init
     loadAutostartState();
 
+    // Terminal for visible scripts on Linux; empty means detect one
+    const terminalInput = document.getElementById('terminal-input');
+
+    invoke('get_terminal')
+        .then(terminal => { terminalInput.value = terminal || ''; })
+        .catch(e => console.error('Failed to get terminal', e));
+
+    document.getElementById('terminal-save-btn').addEventListener('click', async () => {
+        try {
+            await invoke('set_terminal', { terminal: terminalInput.value.trim() || null });
+        } catch (e) {
+            console.error('Failed to set terminal', e);
+            alert('Failed to save terminal: ' + e);
+        }
+    });
+
+    // Show where the config lives (it may be portable or overridden)
+    invoke('get_config_location')
+        .then(path => { document.getElementById('config-location').textContent = path; })
+        .catch(e => console.error('Failed to get config location', e));
+
     // Export/Import config
     const exportBtn = document.getElementById('export-btn');
     const importBtn = document.getElementById('import-btn');
@@ -123,8 +147,46 @@ whenReady(() => {
         }
     });
 
+    // Backups
+    const backupSelect = document.getElementById('backup-select');
+    const restoreBtn = document.getElementById('restore-btn');
+
+    async function loadBackups() {
+        try {
+            const backups = await invoke('list_backups');
+            backupSelect.innerHTML = '';
+            backups.forEach(backup => {
+                const option = document.createElement('option');
+                option.value = backup.name;
+                option.textContent = backup.created || backup.name;
+                backupSelect.appendChild(option);
+            });
+            restoreBtn.disabled = backups.length === 0;
+        } catch (e) {
+            console.error('Failed to list backups', e);
+        }
+    }
+
+    restoreBtn.addEventListener('click', async () => {
+        const name = backupSelect.value;
+        if (!name) return;
+        if (!confirm(`Restore the config saved at ${backupSelect.selectedOptions[0].textContent}? The current config will be backed up first.`)) {
+            return;
+        }
+        try {
+            await invoke('restore_backup', { name });
+            await loadShortcuts();
+            alert('Backup restored.');
+        } catch (e) {
+            console.error('Failed to restore backup', e);
+            alert('Failed to restore backup: ' + e);
+        }
+    });
+
     let shortcuts = [];
-    let editingIndex = -1;
+    let editingId = null;
+    // Steps of the sequence being edited, in config form
+    let editingSteps = [];
 
     // DOM elements
     const list = document.getElementById('shortcut-list');
@@ -145,17 +207,50 @@ whenReady(() => {
     const argsGroup = document.getElementById('args-group');
     const workingDirGroup = document.getElementById('working-dir-group');
     const shellGroup = document.getElementById('shell-group');
+    const shellSelect = document.getElementById('shell');
+    const interpreterGroup = document.getElementById('interpreter-group');
     const hiddenGroup = document.getElementById('hidden-group');
+    const adminGroup = document.getElementById('admin-group');
+    const envGroup = document.getElementById('env-group');
+    const envFileGroup = document.getElementById('env-file-group');
+    const stepsGroup = document.getElementById('steps-group');
+    const stepsList = document.getElementById('steps-list');
+    const notifyGroup = document.getElementById('notify-group');
+    const keepOpenGroup = document.getElementById('keep-open-group');
+    const timeoutGroup = document.getElementById('timeout-group');
     const browseBtn = document.getElementById('browse-btn');
     const browseDirBtn = document.getElementById('browse-dir-btn');
 
+    const configError = document.getElementById('config-error');
+
+    // Show why config.json could not be loaded (e is a ConfigError from the backend)
+    function showConfigError(e) {
+        let location = e.path || '';
+        if (e.line) {
+            location += `:${e.line}:${e.column}`;
+        }
+        document.getElementById('config-error-location').textContent = location;
+        document.getElementById('config-error-message').textContent = e.message || String(e);
+        configError.classList.remove('hidden');
+    }
+
     // Load shortcuts
     async function loadShortcuts() {
         try {
             shortcuts = await invoke('get_shortcuts');
+            // Shortcuts may be the last good config while the file on disk is broken
+            const error = await invoke('get_config_error');
+            if (error) {
+                showConfigError(error);
+            } else {
+                configError.classList.add('hidden');
+            }
             renderList();
+            loadBackups();
         } catch (e) {
             console.error('Failed to load shortcuts', e);
+            showConfigError(e);
+            loadBackups();
         }
     }
 
@@ -177,6 +272,7 @@ whenReady(() => {
             const li = document.createElement('li');
             li.className = 'shortcut-item';
             li.dataset.index = index;
+            li.dataset.id = item.id;
 
             const shortcutType = item.type || 'app';
 
@@ -218,7 +314,7 @@ whenReady(() => {
 
             li.querySelector('.delete-btn').onclick = (e) => {
                 e.stopPropagation();
-                deleteShortcut(index);
+                deleteShortcut(item.id);
             };
 
             list.appendChild(li);
@@ -309,9 +405,9 @@ whenReady(() => {
             item.classList.remove('drag-over-top', 'drag-over-bottom');
         });
 
-        // Find drop target
+        // Find drop target: the item the dragged one should end up in front of
         const items = list.querySelectorAll('.shortcut-item');
-        let newIndex = index;
+        let dropIndex = -1;
 
         for (let i = 0; i < items.length; i++) {
             if (i === index) continue;
@@ -319,15 +415,7 @@ whenReady(() => {
             const rect = items[i].getBoundingClientRect();
             if (e.clientY >= rect.top && e.clientY <= rect.bottom) {
                 const midpoint = rect.top + rect.height / 2;
-                if (e.clientY < midpoint) {
-                    newIndex = i;
-                } else {
-                    newIndex = i + 1;
-                }
-                // Adjust for removal
-                if (index < newIndex) {
-                    newIndex--;
-                }
+                dropIndex = e.clientY < midpoint ? i : i + 1;
                 break;
             }
         }
@@ -335,9 +423,11 @@ whenReady(() => {
         document.removeEventListener('mousemove', onDragMove);
         document.removeEventListener('mouseup', onDragEnd);
 
-        if (newIndex !== index) {
+        // Dropping onto either side of itself leaves the order unchanged
+        if (dropIndex !== -1 && dropIndex !== index && dropIndex !== index + 1) {
+            const beforeId = dropIndex < items.length ? items[dropIndex].dataset.id : null;
             try {
-                await invoke('reorder_shortcut', { fromIndex: index, toIndex: newIndex });
+                await invoke('reorder_shortcut', { id: element.dataset.id, beforeId });
                 await loadShortcuts();
             } catch (err) {
                 console.error('Failed to reorder:', err);
@@ -355,7 +445,15 @@ whenReady(() => {
         argsGroup.classList.remove('hidden');
         workingDirGroup.classList.remove('hidden');
         shellGroup.classList.add('hidden');
+        interpreterGroup.classList.add('hidden');
         hiddenGroup.classList.remove('hidden');
+        adminGroup.classList.remove('hidden');
+        envGroup.classList.remove('hidden');
+        envFileGroup.classList.remove('hidden');
+        stepsGroup.classList.add('hidden');
+        notifyGroup.classList.add('hidden');
+        keepOpenGroup.classList.add('hidden');
+        timeoutGroup.classList.add('hidden');
         browseBtn.classList.remove('hidden');
 
         switch (type) {
@@ -385,19 +483,115 @@ whenReady(() => {
             case 'script':
                 commandLabel.textContent = 'Script File Path';
                 commandInput.placeholder = 'C:\\Scripts\\myscript.ps1';
+                notifyGroup.classList.remove('hidden');
+                keepOpenGroup.classList.remove('hidden');
+                timeoutGroup.classList.remove('hidden');
                 break;
             case 'shell':
                 commandGroup.classList.add('hidden');
                 scriptGroup.classList.remove('hidden');
                 argsGroup.classList.add('hidden');
                 shellGroup.classList.remove('hidden');
+                interpreterGroup.classList.toggle('hidden', shellSelect.value !== 'interpreter');
+                notifyGroup.classList.remove('hidden');
+                keepOpenGroup.classList.remove('hidden');
+                timeoutGroup.classList.remove('hidden');
+                break;
+            case 'sequence':
+                // Each step runs with its own settings
+                commandGroup.classList.add('hidden');
+                argsGroup.classList.add('hidden');
+                workingDirGroup.classList.add('hidden');
+                envGroup.classList.add('hidden');
+                envFileGroup.classList.add('hidden');
+                hiddenGroup.classList.add('hidden');
+                adminGroup.classList.add('hidden');
+                stepsGroup.classList.remove('hidden');
+                renderSteps();
                 break;
         }
     }
 
+    // One row per step: the shortcut it runs and its options
+    function renderSteps() {
+        stepsList.innerHTML = '';
+        editingSteps.forEach((step, index) => {
+            const row = document.createElement('div');
+            row.className = 'step-row';
+
+            const target = document.createElement('div');
+            target.className = 'step-target';
+            if (step.shortcut) {
+                // Inline steps are defined in config.json and kept as they are
+                const label = document.createElement('span');
+                label.className = 'step-inline';
+                label.textContent = `${step.shortcut.name} (inline)`;
+                target.appendChild(label);
+            } else {
+                const select = document.createElement('select');
+                for (const s of shortcuts.filter(s => s.id !== editingId)) {
+                    select.add(new Option(s.name, s.id));
+                }
+                if (step.id && !shortcuts.some(s => s.id === step.id)) {
+                    select.add(new Option(`Missing shortcut (${step.id})`, step.id));
+                }
+                if (step.id) {
+                    select.value = step.id;
+                } else {
+                    step.id = select.value || null;
+                }
+                select.addEventListener('change', () => { step.id = select.value; });
+                target.appendChild(select);
+            }
+            const removeBtn = document.createElement('button');
+            removeBtn.type = 'button';
+            removeBtn.className = 'btn-icon';
+            removeBtn.title = 'Remove step';
+            removeBtn.innerHTML = icons.delete;
+            removeBtn.addEventListener('click', () => {
+                editingSteps.splice(index, 1);
+                renderSteps();
+            });
+            target.appendChild(removeBtn);
+            row.appendChild(target);
+
+            const delay = document.createElement('input');
+            delay.type = 'number';
+            delay.min = '0';
+            delay.step = '100';
+            delay.value = step.delay_ms || 0;
+            delay.addEventListener('change', () => { step.delay_ms = Math.max(0, parseInt(delay.value, 10) || 0); });
+            const delayLabel = document.createElement('label');
+            delayLabel.append('Delay (ms)', delay);
+            row.appendChild(delayLabel);
+
+            row.appendChild(stepOption(step, 'wait', 'Wait for exit'));
+            row.appendChild(stepOption(step, 'continue_on_failure', 'Continue on failure'));
+            row.appendChild(stepOption(step, 'parallel', 'Parallel'));
+            stepsList.appendChild(row);
+        });
+    }
+
+    // Checkbox bound to a boolean option of `step`
+    function stepOption(step, key, text) {
+        const checkbox = document.createElement('input');
+        checkbox.type = 'checkbox';
+        checkbox.checked = step[key] || false;
+        checkbox.addEventListener('change', () => { step[key] = checkbox.checked; });
+        const label = document.createElement('label');
+        label.append(checkbox, text);
+        return label;
+    }
+
+    document.getElementById('add-step-btn').addEventListener('click', () => {
+        editingSteps.push({ id: null, delay_ms: 0, wait: false, continue_on_failure: false, parallel: false });
+        renderSteps();
+    });
+
     // Open modal for adding
     function openAddModal() {
-        editingIndex = -1;
+        editingId = null;
+        editingSteps = [];
         modalTitle.textContent = 'Add Shortcut';
         deleteBtn.classList.add('hidden');
         form.reset();
@@ -407,8 +601,9 @@ whenReady(() => {
 
     // Open modal for editing
     function openEditModal(index) {
-        editingIndex = index;
         const item = shortcuts[index];
+        editingId = item.id;
+        editingSteps = (item.steps || []).map(step => ({ ...step }));
         modalTitle.textContent = 'Edit Shortcut';
         deleteBtn.classList.remove('hidden');
 
@@ -418,12 +613,19 @@ whenReady(() => {
         typeSelect.value = shortcutType;
         document.getElementById('command').value = item.command || '';
         document.getElementById('script').value = item.script || '';
-        document.getElementById('args').value = item.args || '';
+        document.getElementById('args').value = formatArgs(item.args);
         document.getElementById('working_dir').value = item.working_dir || '';
+        document.getElementById('env').value = formatEnv(item.env);
+        document.getElementById('env_file').value = item.env_file || '';
         document.getElementById('description').value = item.description || '';
         document.getElementById('shell').value = item.shell || 'cmd';
+        document.getElementById('interpreter').value = item.interpreter || '';
+        document.getElementById('interpreter_args').value = formatArgs(item.interpreter_args);
         document.getElementById('hidden').checked = item.hidden || false;
         document.getElementById('admin').checked = item.admin || false;
+        document.getElementById('notify_on_exit').checked = item.notify_on_exit || false;
+        document.getElementById('keep_open').checked = item.keep_open || false;
+        document.getElementById('timeout_secs').value = item.timeout_secs || '';
 
         updateFormForType(shortcutType);
         modal.classList.remove('hidden');
@@ -433,7 +635,43 @@ whenReady(() => {
     function closeModal() {
         modal.classList.add('hidden');
         form.reset();
-        editingIndex = -1;
+        editingId = null;
+    }
+
+    // Arguments given as a list are edited as a JSON array
+    function formatArgs(args) {
+        if (Array.isArray(args)) return JSON.stringify(args);
+        return args || '';
+    }
+
+    // A JSON array is kept as a list of arguments, anything else as a command line
+    function parseArgs(text) {
+        const trimmed = (text || '').trim();
+        if (trimmed.startsWith('[')) {
+            try {
+                const list = JSON.parse(trimmed);
+                if (Array.isArray(list) && list.every(a => typeof a === 'string')) return list;
+            } catch (e) {
+                // Not JSON after all, e.g. "[--flag]"; treat it as a command line
+            }
+        }
+        return trimmed || null;
+    }
+
+    // Environment map as KEY=VALUE lines
+    function formatEnv(env) {
+        return Object.entries(env || {}).map(([key, value]) => `${key}=${value}`).join('\n');
+    }
+
+    // KEY=VALUE lines back into a map; blank lines and lines without a key are skipped
+    function parseEnv(text) {
+        const env = {};
+        for (const line of (text || '').split('\n')) {
+            const eq = line.indexOf('=');
+            const key = eq > 0 ? line.slice(0, eq).trim() : '';
+            if (key) env[key] = line.slice(eq + 1).trim();
+        }
+        return env;
     }
 
     // Save shortcut
@@ -442,18 +680,27 @@ whenReady(() => {
 
         const formData = new FormData(form);
         const type = formData.get('type');
+        const usesInterpreter = type === 'shell' && formData.get('shell') === 'interpreter';
 
         const shortcut = {
             name: formData.get('name'),
             type: type,
             command: formData.get('command') || '',
             script: type === 'shell' ? formData.get('script') : null,
-            args: formData.get('args') || null,
+            args: parseArgs(formData.get('args')),
             working_dir: formData.get('working_dir') || null,
+            env: parseEnv(formData.get('env')),
+            env_file: formData.get('env_file').trim() || null,
             description: formData.get('description') || null,
             shell: type === 'shell' ? formData.get('shell') : null,
+            interpreter: usesInterpreter ? formData.get('interpreter').trim() || null : null,
+            interpreter_args: usesInterpreter ? parseArgs(formData.get('interpreter_args')) : null,
             hidden: formData.get('hidden') === 'on',
             admin: formData.get('admin') === 'on',
+            notify_on_exit: (type === 'script' || type === 'shell') && formData.get('notify_on_exit') === 'on',
+            keep_open: (type === 'script' || type === 'shell') && formData.get('keep_open') === 'on',
+            timeout_secs: (type === 'script' || type === 'shell') ? parseInt(formData.get('timeout_secs'), 10) || null : null,
+            steps: type === 'sequence' ? editingSteps.filter(step => step.id || step.shortcut) : [],
         };
 
         // Clean up null/empty optional fields
@@ -462,8 +709,12 @@ whenReady(() => {
         if (!shortcut.description) shortcut.description = null;
 
         try {
-            if (editingIndex >= 0) {
-                await invoke('update_shortcut', { index: editingIndex, shortcut });
+            const warnings = await invoke('shortcut_warnings', { shortcut });
+            if (warnings.length && !confirm(warnings.join('\n') + '\n\nSave anyway?')) {
+                return;
+            }
+            if (editingId) {
+                await invoke('update_shortcut', { id: editingId, shortcut });
             } else {
                 await invoke('add_shortcut', { shortcut });
             }
@@ -476,13 +727,13 @@ whenReady(() => {
     }
 
     // Delete shortcut
-    async function deleteShortcut(index) {
+    async function deleteShortcut(id) {
         if (!confirm('Are you sure you want to delete this shortcut?')) {
             return;
         }
 
         try {
-            await invoke('delete_shortcut', { index });
+            await invoke('delete_shortcut', { id });
             closeModal();
             await loadShortcuts();
         } catch (e) {
@@ -549,8 +800,9 @@ whenReady(() => {
     modalClose.onclick = closeModal;
     cancelBtn.onclick = closeModal;
     form.onsubmit = saveShortcut;
-    deleteBtn.onclick = () => deleteShortcut(editingIndex);
+    deleteBtn.onclick = () => deleteShortcut(editingId);
     typeSelect.onchange = () => updateFormForType(typeSelect.value);
+    shellSelect.onchange = () => updateFormForType(typeSelect.value);
     browseBtn.onclick = browseFile;
     browseDirBtn.onclick = browseFolder;
 
@@ -564,6 +816,16 @@ whenReady(() => {
         }
     });
 
+    // Reload when the config changes on disk or from the popup
+    listen('reload-shortcuts', () => {
+        loadShortcuts();
+    });
+
+    // The file on disk became invalid; keep showing the last good shortcuts
+    listen('config-error', (event) => {
+        showConfigError(event.payload);
+    });
+
     // Initial load
     loadShortcuts();
 });